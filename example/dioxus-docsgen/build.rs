@@ -1,12 +1,28 @@
-use aoike_dioxus::aoike::build::{Entity, post::Post};
+use std::path::Path;
+
+use aoike_dioxus::aoike::build::{
+    Entity, ParseOptions, links, markdown::MarkdownOptions, post::Post,
+};
 
 fn main() {
     println!("cargo:rerun-if-changed=doc-src");
 
     // Parse markdown files to HTML using aoike-build
-    let posts = aoike_dioxus::aoike::build::parse_posts("doc-src/posts");
-    let index = Entity::new("doc-src/index.md");
-    let index = aoike_dioxus::build::DioxusPost::from(Post::try_from(index).unwrap());
+    let markdown = MarkdownOptions::default().with_wikilinks(true);
+    let options = ParseOptions::default().with_markdown(markdown);
+    let posts = aoike_dioxus::aoike::build::parse_posts_with_options("doc-src/posts", &options);
+    let index = Entity::new("doc-src/index.md").unwrap();
+    let mut index = Post::parse(index, &options).unwrap();
+    let mut sections =
+        aoike_dioxus::aoike::build::section::parse_sections_with_options("doc-src/posts", &options);
+    links::resolve_page_links(
+        Path::new("doc-src/posts"),
+        &options,
+        &posts,
+        &mut index,
+        &mut sections,
+    )
+    .emit();
 
     // Convert to Dioxus posts and generate RSX code
     let dioxus_posts: Vec<_> = posts
@@ -16,6 +32,6 @@ fn main() {
     let dioxus_index = aoike_dioxus::build::DioxusPost::from(index);
 
     let out_dir = std::env::current_dir().unwrap().join("src");
-    let code = aoike_dioxus::build::generate_code(dioxus_posts, dioxus_index);
+    let code = aoike_dioxus::build::generate_code(dioxus_posts, dioxus_index, sections);
     std::fs::write(out_dir.join("docsgen.rs"), code).unwrap();
 }
@@ -0,0 +1,11 @@
+//! Pre-render the site into `dist` after `dx build`
+
+#[path = "../config.rs"]
+mod config;
+
+fn main() {
+    let dist = std::env::args().nth(1).unwrap_or("dist".to_string());
+    let written = aoike_dioxus::ssg::prerender(&dist, &config::config(), &config::site())
+        .expect("failed to pre-render the site");
+    println!("pre-rendered {} pages into {dist}", written.len());
+}
@@ -1,14 +1,37 @@
-use aoike::build::{post::Post, utils::patch_file, Entity};
+use std::path::Path;
+
+use aoike::build::{
+    feed::{self, FeedConfig},
+    links,
+    markdown::MarkdownOptions,
+    post::Post,
+    section,
+    sitemap::{self, SitemapConfig},
+    utils::patch_file,
+    Entity, ParseOptions,
+};
+use aoike_sycamore::build::HighlightTheme;
 
 fn main() {
     println!("cargo:rerun-if-changed=doc-src");
 
-    aoike_sycamore::build::init_aoike_sycamore();
+    aoike_sycamore::build::init_aoike_sycamore_with_highlight(HighlightTheme::Auto);
 
     // Parse markdown files to HTML using aoike-build
-    let posts = aoike::build::parse_posts("doc-src/posts");
-    let index = Entity::new("doc-src/index.md");
-    let index = Post::try_from(index).unwrap();
+    let markdown = MarkdownOptions::default().with_wikilinks(true);
+    let options = ParseOptions::default().with_markdown(markdown);
+    let posts = aoike::build::parse_posts_with_options("doc-src/posts", &options);
+    let index = Entity::new("doc-src/index.md").unwrap();
+    let mut index = Post::parse(index, &options).unwrap();
+    let mut sections = section::parse_sections_with_options("doc-src/posts", &options);
+    links::resolve_page_links(
+        Path::new("doc-src/posts"),
+        &options,
+        &posts,
+        &mut index,
+        &mut sections,
+    )
+    .emit();
 
     let assets = aoike::build::get_assets_trunk_data(&posts, &index, "doc-src");
     patch_file(
@@ -18,9 +41,35 @@ fn main() {
         Some("</head>"),
     )
     .unwrap();
+
+    let feed_config = FeedConfig::new(
+        "冰弦のBlog".to_string(),
+        "https://azurice.github.io".to_string(),
+    )
+    .with_author("Azur冰弦".to_string())
+    .with_description("『看清世界的真相后仍热爱生活』".to_string());
+    feed::write_feeds(&posts, &feed_config, "generated").unwrap();
+    patch_file(
+        "index.html",
+        &feed::get_feeds_trunk_data(&feed_config, "generated"),
+        "AOIKE_SYCAMORE_FEEDS",
+        Some("</head>"),
+    )
+    .unwrap();
+
+    let sitemap_config = SitemapConfig::new("https://azurice.github.io".to_string());
+    sitemap::write_sitemap(&posts, &index, &sitemap_config, "generated").unwrap();
+    patch_file(
+        "index.html",
+        &sitemap::get_sitemap_trunk_data("generated"),
+        "AOIKE_SYCAMORE_SITEMAP",
+        Some("</head>"),
+    )
+    .unwrap();
+
     let out_dir = std::env::current_dir().unwrap().join("src");
-    let code = std::fs::read_to_string(out_dir.join("docsgen.rs")).unwrap_or(String::new());
-    let gen_code = aoike::build::generate_code(posts, index);
+    let code = std::fs::read_to_string(out_dir.join("docsgen.rs")).unwrap_or_default();
+    let gen_code = aoike::build::generate_code(posts, index, sections);
     if code != gen_code {
         std::fs::write(out_dir.join("docsgen.rs"), gen_code).unwrap();
     }
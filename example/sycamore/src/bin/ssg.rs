@@ -0,0 +1,24 @@
+//! Pre-render the site into `dist` after `trunk build`
+
+#[path = "../config.rs"]
+mod config;
+#[path = "../docsgen.rs"]
+mod docsgen;
+
+use aoike::Site;
+
+fn main() {
+    let dist = std::env::args().nth(1).unwrap_or("dist".to_string());
+    let site = Site {
+        posts: docsgen::posts(),
+        index: docsgen::index(),
+        tags: docsgen::tags(),
+        categories: docsgen::categories(),
+        sections: docsgen::sections(),
+        search_index: docsgen::search_index(),
+    };
+
+    let written = aoike_sycamore::ssg::prerender(&dist, &config::config(), &site)
+        .expect("failed to pre-render the site");
+    println!("pre-rendered {} pages into {dist}", written.len());
+}
@@ -4,10 +4,15 @@ use std::any::Any;
 
 use dioxus::{core::Element, prelude::*};
 
+use aoike::{search::SearchResult, TaxonomyTerm};
+
 use crate::{
     app::layout::Base,
-    components::giscus::{Giscus, GiscusOptions},
-    PostData, RsxFn, Site,
+    components::{
+        giscus::{Giscus, GiscusOptions},
+        toc::Toc,
+    },
+    PostData, RsxFn, SectionData, Site,
 };
 
 pub trait App {
@@ -18,6 +23,13 @@ pub trait App {
     where
         Self: Sized,
     {
+        // 清掉预渲染的内容，由客户端重新渲染
+        if let Some(main) = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id("main"))
+        {
+            main.set_inner_html("");
+        }
         self.builder().launch(Self::app);
     }
 }
@@ -86,18 +98,36 @@ enum Route {
     Home,
     #[route("/posts")]
     Posts,
-    #[route("/posts/:slug")]
-    Post { slug: String },
+    /// A post or a section, like `/posts/rust/async-md` or `/posts/rust`
+    #[route("/posts/:..path")]
+    Post { path: Vec<String> },
+    #[route("/tags")]
+    Tags,
+    #[route("/tags/:tag")]
+    Tag { tag: String },
+    #[route("/categories")]
+    Categories,
+    #[route("/categories/:category")]
+    Category { category: String },
+    #[route("/search")]
+    Search,
     #[route("/404")]
     NotFound,
 }
 
 #[component]
 pub fn Posts() -> Element {
-    let posts = consume_context::<Site>().posts;
+    let site = consume_context::<Site>();
+    let posts = site.posts;
+    let sections = site
+        .sections
+        .iter()
+        .filter(|section| !section.slug.contains('/'))
+        .collect::<Vec<_>>();
 
     rsx! {
         h1 { "所有文章" }
+        SectionList { sections }
         for post in posts {
             PostCard { post }
         }
@@ -105,28 +135,277 @@ pub fn Posts() -> Element {
 }
 
 #[component]
-pub fn Post(slug: String) -> Element {
+pub fn Tags() -> Element {
+    let tags = consume_context::<Site>().tags;
+
+    rsx! {
+        Terms { title: "所有标签", base: "/tags", terms: tags }
+    }
+}
+
+#[component]
+pub fn Tag(tag: String) -> Element {
+    let tags = consume_context::<Site>().tags;
+
+    rsx! {
+        TermPosts { title: "标签", terms: tags, slug: tag }
+    }
+}
+
+#[component]
+pub fn Categories() -> Element {
+    let categories = consume_context::<Site>().categories;
+
+    rsx! {
+        Terms { title: "所有分类", base: "/categories", terms: categories }
+    }
+}
+
+#[component]
+pub fn Category(category: String) -> Element {
+    let categories = consume_context::<Site>().categories;
+
+    rsx! {
+        TermPosts { title: "分类", terms: categories, slug: category }
+    }
+}
+
+#[component]
+pub fn Terms(title: &'static str, base: &'static str, terms: &'static [TaxonomyTerm]) -> Element {
+    rsx! {
+        h1 { "{title}" }
+        div {
+            class: "w-full flex flex-wrap gap-2",
+            for term in terms {
+                Link {
+                    class: "flex gap-1 p-x-2 p-y-1 rounded border border-slate-200 hover:border-slate-400",
+                    to: format!("{}/{}", base, term.slug),
+                    span { "{term.name}" }
+                    span { class: "text-gray-400", "{term.posts.len()}" }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn TermPosts(title: &'static str, terms: &'static [TaxonomyTerm], slug: String) -> Element {
     let posts = consume_context::<Site>().posts;
-    let config = consume_context::<ConfigContext>();
 
-    let post = posts.iter().find(|b| b.slug == slug);
-    let Some(post) = post else {
+    let Some(term) = terms.iter().find(|t| t.slug == slug) else {
+        navigator().replace(Route::NotFound);
+        return rsx! {};
+    };
+    let term_posts = term
+        .posts
+        .iter()
+        .filter_map(|slug| posts.iter().find(|p| &p.slug == slug));
+
+    rsx! {
+        h1 { "{title}: {term.name}" }
+        for post in term_posts {
+            PostCard { post }
+        }
+    }
+}
+
+#[component]
+pub fn Section(slug: String) -> Element {
+    let site = consume_context::<Site>();
+
+    let Some(section) = site.sections.iter().find(|s| s.slug == slug) else {
         navigator().replace(Route::NotFound);
         return rsx! {};
     };
+    let mut parents = section
+        .slug
+        .split('/')
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    parents.pop();
+    let subsections = section
+        .sections
+        .iter()
+        .filter_map(|slug| site.sections.iter().find(|s| &s.slug == slug))
+        .collect::<Vec<_>>();
+    let section_posts = section
+        .posts
+        .iter()
+        .filter_map(|slug| site.posts.iter().find(|p| &p.slug == slug));
 
     rsx! {
+        Breadcrumbs { path: parents }
+        div {
+            class: "markdown w-full",
+            {section.content_rsx.as_ref()()}
+        }
+        SectionList { sections: subsections }
+        for post in section_posts {
+            PostCard { post }
+        }
+    }
+}
+
+/// Links to `sections`, with the number of posts directly in each
+#[component]
+pub fn SectionList(sections: Vec<&'static SectionData>) -> Element {
+    rsx! {
+        if !sections.is_empty() {
+            div {
+                class: "w-full flex flex-wrap gap-2",
+                for section in sections {
+                    Link {
+                        class: "flex gap-1 p-x-2 p-y-1 rounded border border-slate-200 hover:border-slate-400",
+                        to: format!("/posts/{}", section.slug),
+                        span { "📁 {section.title}" }
+                        span { class: "text-gray-400", "{section.posts.len()}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `所有文章 / Rust / Async`, `path` is the slugified directory names
+#[component]
+pub fn Breadcrumbs(path: Vec<String>) -> Element {
+    let sections = consume_context::<Site>().sections;
+    let crumbs = (1..=path.len()).filter_map(|i| {
+        let slug = path[..i].join("/");
+        sections.iter().find(|s| s.slug == slug)
+    });
+
+    rsx! {
+        nav {
+            class: "w-full flex gap-1 text-sm text-gray-400",
+            Link { class: "hover:underline", to: "/posts", "所有文章" }
+            for section in crumbs {
+                span { "/" }
+                Link {
+                    class: "hover:underline",
+                    to: format!("/posts/{}", section.slug),
+                    "{section.title}"
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn Search() -> Element {
+    let search_index = consume_context::<Site>().search_index;
+    let mut query = use_signal(String::new);
+    let results = use_memo(move || search_index.search(&query(), 20));
+
+    rsx! {
+        h1 { "搜索" }
+        input {
+            class: "w-full p-2 rounded border border-slate-200 hover:border-slate-400",
+            r#type: "search",
+            placeholder: "搜索文章…",
+            value: "{query}",
+            oninput: move |evt| query.set(evt.value()),
+        }
+        if !query().trim().is_empty() && results().is_empty() {
+            p { class: "text-gray-400", "没有找到相关的文章" }
+        }
+        for result in results() {
+            SearchResultCard { result }
+        }
+    }
+}
+
+#[component]
+pub fn SearchResultCard(result: SearchResult) -> Element {
+    rsx! {
+        div {
+            class: "w-full flex flex-col gap-2 p-2 rounded border border-slate-200 hover:border-slate-400",
+            Link {
+                to: format!("/posts/{}", result.doc.slug),
+                h2 { "{result.doc.title}" }
+            }
+            p {
+                class: "text-sm text-gray-600",
+                for (text, highlighted) in result.snippet {
+                    if highlighted {
+                        mark { "{text}" }
+                    } else {
+                        "{text}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn Post(path: Vec<String>) -> Element {
+    let posts = consume_context::<Site>().posts;
+    let config = consume_context::<ConfigContext>();
+
+    let slug = path.join("/");
+    let Some(post) = posts.iter().find(|b| b.slug == slug) else {
+        if let Some(post) = posts.iter().find(|p| p.aliases.contains(&slug)) {
+            navigator().replace(format!("/posts/{}", post.slug));
+            return rsx! {};
+        }
+        return rsx! { Section { slug } };
+    };
+
+    rsx! {
+        if !post.section.is_empty() {
+            Breadcrumbs { path: post.section.clone() }
+        }
+
+        if !post.toc.is_empty() {
+            Toc { toc: &post.toc }
+        }
+
         div {
             class: "markdown",
             {post.content_rsx.as_ref()()}
         }
 
+        Backlinks { post }
+
         {config.giscus_options.map(|options|
             rsx! { Giscus { options } }
         )}
     }
 }
 
+/// The posts linking to `post`
+#[component]
+pub fn Backlinks(post: &'static PostData) -> Element {
+    let posts = consume_context::<Site>().posts;
+    let backlinks = post
+        .backlinks
+        .iter()
+        .filter_map(|slug| posts.iter().find(|p| &p.slug == slug))
+        .collect::<Vec<_>>();
+
+    rsx! {
+        if !backlinks.is_empty() {
+            div {
+                class: "w-full flex flex-col gap-2 p-2 rounded border border-slate-200",
+                h2 { class: "text-sm text-gray-400", "反向链接" }
+                ul {
+                    class: "flex flex-col gap-1",
+                    for post in backlinks {
+                        li {
+                            Link {
+                                class: "underline hover:underline-gray-400",
+                                to: format!("/posts/{}", post.slug),
+                                "{post.title}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 // MARK: Notfound
 #[component]
 pub fn NotFound() -> Element {
@@ -291,6 +570,9 @@ pub fn PostCard(post: &'static PostData) -> Element {
                     class: "text-xs text-gray-400",
                     "更新日期: {post.updated.year()}-{u8::from(post.updated.month())}-{post.updated.day()}"
                 }
+                if post.uncommitted {
+                    span { class: "text-xs text-orange-400", "未提交" }
+                }
             }
             div { class: "summary", {post.summary_rsx.as_ref()()} }
         }
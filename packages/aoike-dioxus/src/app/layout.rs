@@ -25,14 +25,23 @@ pub fn Head() -> Element {
     }
 }
 
+/// Provided when pre-rendering, [`Base`] then renders the content right away
+#[cfg(feature = "ssr")]
+#[derive(Clone, Copy)]
+pub(crate) struct Prerendering;
+
 // MARK: Base
 #[component]
 pub fn Base() -> Element {
-    let mounted = use_signal(|| false);
+    #[cfg(feature = "ssr")]
+    let prerendering = try_consume_context::<Prerendering>().is_some();
+    #[cfg(not(feature = "ssr"))]
+    let prerendering = false;
+    let mounted = use_signal(|| prerendering);
 
     // INFO: Temporary solution to avoid flash of unstyled content
     {
-        let mut mounted = mounted.clone();
+        let mut mounted = mounted;
         use_effect(move || {
             info!("use effect");
             wasm_bindgen_futures::spawn_local(async move {
@@ -108,6 +117,12 @@ pub fn Header() -> Element {
                     "文章"
                 }
 
+                a {
+                    class: "h-10 gap-1 nav-btn text-sm p-x-4",
+                    href: "/tags",
+                    "标签"
+                }
+
                 a {
                     class: "h-10 gap-1 nav-btn text-sm p-x-4",
                     href: "/search",
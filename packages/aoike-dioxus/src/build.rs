@@ -1,33 +1,60 @@
-use aoike::build::post::Post;
+use aoike::{
+    build::{
+        post::Post,
+        section::{self, Section},
+    },
+    MetaValue, SectionData, TocEntry,
+};
 use proc_macro2::TokenStream;
 use quote::ToTokens;
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 pub fn html_to_rsx(html: &str) -> String {
-    let dom = dioxus_rsx_rosetta::Dom::parse(html).unwrap();
+    // 文本和属性在 rsx 中都是格式化字符串，需要转义花括号
+    let html = html.replace('{', "{{").replace('}', "}}");
+    let dom = dioxus_rsx_rosetta::Dom::parse(&html).unwrap();
     let rsx = dioxus_rsx_rosetta::rsx_from_html(&dom);
-    let rsx = dioxus_autofmt::write_block_out(&rsx).unwrap();
-    rsx
+    dioxus_autofmt::write_block_out(&rsx).unwrap()
 }
 
 pub struct DioxusPost {
     pub slug: String,
+    /// Names of the directories of the post, see [`aoike::build::Entity::section`]
+    pub section: Vec<String>,
+    /// See [`Post::aliases`]
+    pub aliases: Vec<String>,
+    pub backlinks: Vec<String>,
     pub title: String,
     pub summary_html: String,
     pub content_html: String,
     pub created: i64,
     pub updated: i64,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    pub draft: bool,
+    pub uncommitted: bool,
+    pub extra: BTreeMap<String, MetaValue>,
+    pub toc: Vec<TocEntry>,
 }
 
 impl From<Post> for DioxusPost {
     fn from(post:Post) -> Self {
         Self {
             slug: post.slug(),
+            section: post.entity.section.clone(),
+            aliases: post.aliases(),
             created: post.created.unix_timestamp(),
             updated: post.updated.unix_timestamp(),
             title: post.title,
             summary_html: post.summary_html,
             content_html: post.content_html,
+            tags: post.meta.tags,
+            categories: post.meta.categories,
+            draft: post.meta.draft,
+            uncommitted: post.entity.uncommitted,
+            extra: post.meta.extra,
+            toc: post.toc,
+            backlinks: post.backlinks,
         }
     }
 }
@@ -36,29 +63,108 @@ impl ToTokens for DioxusPost {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self {
             slug,
+            section,
+            aliases,
+            backlinks,
             title,
             summary_html,
             content_html,
             created,
             updated,
+            tags,
+            categories,
+            draft,
+            uncommitted,
+            extra,
+            toc,
         } = self;
-        let summary_rsx = TokenStream::from_str(&html_to_rsx(&summary_html)).unwrap();
-        let content_rsx = TokenStream::from_str(&html_to_rsx(&content_html)).unwrap();
+        let section = section
+            .iter()
+            .map(|name| section::section_slug(std::slice::from_ref(name)));
+        let extra_keys = extra.keys();
+        let extra_values = extra.values();
+        let summary_rsx = TokenStream::from_str(&html_to_rsx(summary_html)).unwrap();
+        let content_rsx = TokenStream::from_str(&html_to_rsx(content_html)).unwrap();
         tokens.extend(quote::quote! {
             aoike_dioxus::PostData {
                 title: #title.to_string(),
                 slug: #slug.to_string(),
+                section: vec![#(#section.to_string()),*],
+                aliases: vec![#(#aliases.to_string()),*],
+                backlinks: vec![#(#backlinks.to_string()),*],
                 summary_rsx: aoike_dioxus::RsxFn::new(|| rsx! { #summary_rsx }),
                 content_rsx: aoike_dioxus::RsxFn::new(|| rsx! { #content_rsx }),
                 created: aoike_dioxus::time::UtcDateTime::from_unix_timestamp(#created).unwrap(),
                 updated: aoike_dioxus::time::UtcDateTime::from_unix_timestamp(#updated).unwrap(),
+                tags: vec![#(#tags.to_string()),*],
+                categories: vec![#(#categories.to_string()),*],
+                draft: #draft,
+                uncommitted: #uncommitted,
+                extra: [#((#extra_keys.to_string(), #extra_values)),*].into_iter().collect(),
+                toc: vec![#(#toc),*],
             }
         });
     }
 }
 
-pub fn generate_code(posts: Vec<DioxusPost>, index: DioxusPost) -> String {
+/// [`SectionData`] converted to `aoike_dioxus::SectionData`
+pub struct DioxusSection(pub SectionData);
+
+impl ToTokens for DioxusSection {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let SectionData {
+            slug,
+            title,
+            content_html,
+            posts,
+            sections,
+        } = &self.0;
+        // 没有 `_index` 页面的 section 只显示标题
+        let content_html = if content_html.is_empty() {
+            format!("<h1>{}</h1>", aoike::build::utils::escape_xml(title))
+        } else {
+            content_html.clone()
+        };
+        let content_rsx = TokenStream::from_str(&html_to_rsx(&content_html)).unwrap();
+        tokens.extend(quote::quote! {
+            aoike_dioxus::SectionData {
+                slug: #slug.to_string(),
+                title: #title.to_string(),
+                content_rsx: aoike_dioxus::RsxFn::new(|| rsx! { #content_rsx }),
+                posts: vec![#(#posts.to_string()),*],
+                sections: vec![#(#sections.to_string()),*],
+            }
+        });
+    }
+}
+
+/// `sections` are the `_index` pages from [`section::parse_sections`]
+pub fn generate_code(posts: Vec<DioxusPost>, index: DioxusPost, sections: Vec<Section>) -> String {
+    let tags = aoike::build::taxonomy::collect_terms(
+        posts
+            .iter()
+            .map(|p| (p.slug.clone(), p.created, p.tags.as_slice())),
+    );
+    let categories = aoike::build::taxonomy::collect_terms(
+        posts
+            .iter()
+            .map(|p| (p.slug.clone(), p.created, p.categories.as_slice())),
+    );
+    let sections = section::collect_sections(
+        posts
+            .iter()
+            .map(|p| (p.slug.clone(), p.created, p.section.as_slice())),
+        &sections,
+    )
+    .into_iter()
+    .map(DioxusSection);
+    let search_index = aoike::build::search::build_search_index(
+        posts
+            .iter()
+            .map(|p| (p.slug.clone(), p.title.as_str(), p.content_html.as_str())),
+    );
     let token = quote::quote! {
+        use aoike_dioxus::aoike;
         use dioxus::prelude::*;
 
         pub fn index() -> &'static aoike_dioxus::PostData {
@@ -70,11 +176,33 @@ pub fn generate_code(posts: Vec<DioxusPost>, index: DioxusPost) -> String {
         pub fn posts() -> &'static [aoike_dioxus::PostData] {
             static POSTS: std::sync::LazyLock<Vec<aoike_dioxus::PostData>> = std::sync::LazyLock::new(|| {
                 let mut posts = vec![#(#posts),*];
-                posts.sort_by(|a, b| b.created.cmp(&a.created));
+                posts.sort_by_key(|p| std::cmp::Reverse(p.created));
                 posts
             });
             &POSTS
         }
+        pub fn tags() -> &'static [aoike::TaxonomyTerm] {
+            static TAGS: std::sync::LazyLock<Vec<aoike::TaxonomyTerm>> = std::sync::LazyLock::new(|| {
+                vec![#(#tags),*]
+            });
+            &TAGS
+        }
+        pub fn categories() -> &'static [aoike::TaxonomyTerm] {
+            static CATEGORIES: std::sync::LazyLock<Vec<aoike::TaxonomyTerm>> = std::sync::LazyLock::new(|| {
+                vec![#(#categories),*]
+            });
+            &CATEGORIES
+        }
+        pub fn sections() -> &'static [aoike_dioxus::SectionData] {
+            static SECTIONS: std::sync::LazyLock<Vec<aoike_dioxus::SectionData>> = std::sync::LazyLock::new(|| {
+                vec![#(#sections),*]
+            });
+            &SECTIONS
+        }
+        pub fn search_index() -> &'static aoike::search::SearchIndex {
+            static SEARCH_INDEX: aoike::search::SearchIndex = #search_index;
+            &SEARCH_INDEX
+        }
     };
 
     prettyplease::unparse(&syn::parse_quote! {
@@ -0,0 +1,76 @@
+use aoike::TocEntry;
+use dioxus::{core::Element, prelude::*};
+
+/// 监听滚动，把当前阅读的标题 id 发送回来
+const SCROLL_SPY: &str = r#"
+const ids = await dioxus.recv();
+let last = null;
+const update = () => {
+    let current = "";
+    for (const id of ids) {
+        const el = document.getElementById(id);
+        if (el && el.getBoundingClientRect().top <= 96) {
+            current = id;
+        }
+    }
+    if (current !== last) {
+        last = current;
+        dioxus.send(current);
+    }
+};
+window.addEventListener("scroll", update, { passive: true });
+update();
+"#;
+
+/// Sticky table of contents of a post, highlighting the heading being read
+#[component]
+pub fn Toc(toc: &'static [TocEntry]) -> Element {
+    let mut active = use_signal(String::new);
+
+    use_effect(move || {
+        spawn(async move {
+            let mut eval = document::eval(SCROLL_SPY);
+            let _ = eval.send(flatten_ids(toc));
+            while let Ok(id) = eval.recv::<String>().await {
+                active.set(id);
+            }
+        });
+    });
+
+    rsx! {
+        nav {
+            class: "toc",
+            p { class: "toc-title", "目录" }
+            TocList { entries: toc, active }
+        }
+    }
+}
+
+#[component]
+fn TocList(entries: &'static [TocEntry], active: Signal<String>) -> Element {
+    rsx! {
+        ul {
+            for entry in entries {
+                li {
+                    key: "{entry.id}",
+                    a {
+                        href: "#{entry.id}",
+                        class: if active() == entry.id { "active" },
+                        "{entry.title}"
+                    }
+                    if !entry.children.is_empty() {
+                        TocList { entries: &entry.children, active }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Ids of the headings in document order
+fn flatten_ids(entries: &[TocEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .flat_map(|entry| std::iter::once(entry.id.clone()).chain(flatten_ids(&entry.children)))
+        .collect()
+}
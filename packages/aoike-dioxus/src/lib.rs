@@ -3,13 +3,17 @@ pub use aoike;
 pub mod app;
 pub mod components {
     pub mod giscus;
+    pub mod toc;
 }
 
 #[cfg(feature = "build")]
 pub mod build;
+#[cfg(feature = "ssr")]
+pub mod ssg;
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
+use aoike::{search::SearchIndex, MetaValue, TaxonomyTerm, TocEntry};
 use dioxus::prelude::*;
 pub use time;
 use time::UtcDateTime;
@@ -39,14 +43,40 @@ impl PartialEq for RsxFn {
 pub struct PostData {
     pub title: String,
     pub slug: String,
+    /// Slugified names of the directories of the post, like `["rust", "async"]`
+    pub section: Vec<String>,
+    /// Former slugs of the post, `/posts/{alias}` redirects to it
+    pub aliases: Vec<String>,
+    /// Slugs of the posts linking to this one
+    pub backlinks: Vec<String>,
     pub summary_rsx: RsxFn,
     pub content_rsx: RsxFn,
     pub created: UtcDateTime,
     pub updated: UtcDateTime,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    pub draft: bool,
+    pub uncommitted: bool,
+    pub extra: BTreeMap<String, MetaValue>,
+    pub toc: Vec<TocEntry>,
+}
+
+/// [`aoike::SectionData`] with the `_index` page rendered as rsx
+#[derive(Props, Clone, PartialEq)]
+pub struct SectionData {
+    pub slug: String,
+    pub title: String,
+    pub content_rsx: RsxFn,
+    pub posts: Vec<String>,
+    pub sections: Vec<String>,
 }
 
 #[derive(Clone)]
 pub struct Site {
     pub posts: &'static [PostData],
     pub index: &'static PostData,
+    pub tags: &'static [TaxonomyTerm],
+    pub categories: &'static [TaxonomyTerm],
+    pub sections: &'static [SectionData],
+    pub search_index: &'static SearchIndex,
 }
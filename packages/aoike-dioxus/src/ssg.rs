@@ -0,0 +1,186 @@
+//! Pre-render every route of [`AoikeApp`](crate::app::AoikeApp) into standalone html files
+//!
+//! This runs after `dx build`, it takes the `index.html` dx generated as the template
+//! and writes the rendered page of each route into its `#main` element. The client
+//! clears it and renders the app again when wasm loads.
+
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use dioxus::{
+    history::{History, MemoryHistory},
+    prelude::*,
+};
+
+use crate::{
+    app::{layout::Prerendering, AoikeApp, App, ConfigContext},
+    Site,
+};
+
+/// All the routes of the site
+pub fn routes(site: &Site) -> Vec<String> {
+    let mut routes = vec!["/".to_string(), "/posts".to_string()];
+    routes.extend(site.posts.iter().map(|p| format!("/posts/{}", p.slug)));
+    routes.extend(site.sections.iter().map(|s| format!("/posts/{}", s.slug)));
+    routes.push("/tags".to_string());
+    routes.extend(site.tags.iter().map(|t| format!("/tags/{}", t.slug)));
+    routes.push("/categories".to_string());
+    routes.extend(
+        site.categories
+            .iter()
+            .map(|t| format!("/categories/{}", t.slug)),
+    );
+    routes.push("/search".to_string());
+    routes.push("/404".to_string());
+    routes
+}
+
+/// The `(alias, target)` routes of the renamed posts
+pub fn redirects(site: &Site) -> Vec<(String, String)> {
+    site.posts
+        .iter()
+        .flat_map(|p| {
+            p.aliases
+                .iter()
+                .map(|alias| (format!("/posts/{alias}"), format!("/posts/{}", p.slug)))
+        })
+        .collect()
+}
+
+/// A page redirecting to `target`, without loading the app
+fn redirect_page(target: &str) -> String {
+    let target = escape_html(target).replace('"', "&quot;");
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="0; url={target}">
+<link rel="canonical" href="{target}">
+</head>
+<body><a href="{target}">{target}</a></body>
+</html>
+"#
+    )
+}
+
+/// Render the page at `path`
+pub fn render_route(path: &str, config: &ConfigContext, site: &Site) -> String {
+    let history: Rc<dyn History> = Rc::new(MemoryHistory::with_initial_path(path));
+    let mut vdom = VirtualDom::new(AoikeApp::app)
+        .with_root_context(site.clone())
+        .with_root_context(config.clone())
+        .with_root_context(history)
+        .with_root_context(Prerendering);
+    vdom.rebuild_in_place();
+    dioxus::ssr::render(&vdom)
+}
+
+/// The title of the page at `path`
+fn page_title(path: &str, config: &ConfigContext, site: &Site) -> String {
+    let site_title = config.title.as_deref().unwrap_or("Site Title");
+    let page = if let Some(slug) = path.strip_prefix("/posts/") {
+        site.posts
+            .iter()
+            .find(|p| p.slug == slug)
+            .map(|p| p.title.clone())
+            .or_else(|| {
+                site.sections
+                    .iter()
+                    .find(|s| s.slug == slug)
+                    .map(|s| s.title.clone())
+            })
+    } else if let Some(slug) = path.strip_prefix("/tags/") {
+        site.tags
+            .iter()
+            .find(|t| t.slug == slug)
+            .map(|t| t.name.clone())
+    } else if let Some(slug) = path.strip_prefix("/categories/") {
+        site.categories
+            .iter()
+            .find(|t| t.slug == slug)
+            .map(|t| t.name.clone())
+    } else {
+        match path {
+            "/posts" => Some("所有文章".to_string()),
+            "/tags" => Some("所有标签".to_string()),
+            "/categories" => Some("所有分类".to_string()),
+            "/search" => Some("搜索".to_string()),
+            "/404" => Some("404 Not Found".to_string()),
+            _ => None,
+        }
+    };
+    match page {
+        Some(page) => format!("{page} | {site_title}"),
+        None => site_title.to_string(),
+    }
+}
+
+/// Replace the content of `<title>` and of the `#main` element in the template
+fn fill_template(template: &str, title: &str, main: &str) -> String {
+    let mut html = template.to_string();
+    if let (Some(start), Some(end)) = (html.find("<title>"), html.find("</title>")) {
+        html.replace_range(start + "<title>".len()..end, &escape_html(title));
+    }
+    // dx 生成的模板中是一个空的 `<div id="main"></div>`
+    if let Some(main_start) = html.find(r#"id="main""#)
+        && let Some(open_end) = html[main_start..].find('>')
+        && let Some(end) = html[main_start..].find("</div>")
+    {
+        html.replace_range(main_start + open_end + 1..main_start + end, main);
+    }
+    html
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// The output file of `path`, `/404` is written to `404.html` so that static hosts
+/// like GitHub Pages can pick it up
+fn output_path(dist: &Path, path: &str) -> PathBuf {
+    match path.trim_matches('/') {
+        "" => dist.join("index.html"),
+        "404" => dist.join("404.html"),
+        path => dist.join(path).join("index.html"),
+    }
+}
+
+/// Pre-render every route into `dist`, using `dist/index.html` as the template, and
+/// write a redirect page for every alias of the posts
+///
+/// Returns the paths of the written files.
+pub fn prerender(
+    dist: impl AsRef<Path>,
+    config: &ConfigContext,
+    site: &Site,
+) -> std::io::Result<Vec<PathBuf>> {
+    let dist = dist.as_ref();
+    let template = std::fs::read_to_string(dist.join("index.html"))?;
+
+    let mut written = Vec::new();
+    for path in routes(site) {
+        let main = render_route(&path, config, site);
+        let html = fill_template(&template, &page_title(&path, config, site), &main);
+
+        let output = output_path(dist, &path);
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&output, html)?;
+        written.push(output);
+    }
+    for (alias, target) in redirects(site) {
+        let output = output_path(dist, &alias);
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&output, redirect_page(&target))?;
+        written.push(output);
+    }
+    Ok(written)
+}
@@ -23,7 +23,7 @@ fn main() {
         &mut walkdir::WalkDir::new("css")
             .into_iter()
             .filter_map(|e| e.ok()),
-        &Path::new("css"),
+        Path::new("css"),
         File::create("css.zip").expect("failed to create css.zip"),
         zip::CompressionMethod::Deflated,
         &sha1_hash,
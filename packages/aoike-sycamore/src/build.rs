@@ -5,10 +5,40 @@ use aoike::build::utils::patch_file;
 // const CSS_ASSETS: Dir<'_> = include_dir::include_dir!("packages/aoike-sycamore/css");
 const CSS_ARCHIVE: &[u8] = include_bytes!("../css.zip");
 
+/// Stylesheet for the code blocks highlighted by `aoike::build::highlight`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HighlightTheme {
+    /// `InspiredGitHub`
+    #[default]
+    Light,
+    /// `base16-ocean.dark`
+    Dark,
+    /// Follows `prefers-color-scheme`
+    Auto,
+    /// No token colors
+    None,
+}
+
+impl HighlightTheme {
+    fn css_path(&self) -> Option<&'static str> {
+        match self {
+            Self::Light => Some("static/css/highlight/light.css"),
+            Self::Dark => Some("static/css/highlight/dark.css"),
+            Self::Auto => Some("static/css/highlight/auto.css"),
+            Self::None => None,
+        }
+    }
+}
+
 /// This does two things:
 /// 1. export css assets to `assets/css`
 /// 2. insert `<link>` tag into `index.html`
 pub fn init_aoike_sycamore() {
+    init_aoike_sycamore_with_highlight(HighlightTheme::default());
+}
+
+/// Same as [`init_aoike_sycamore`], with the given highlight theme
+pub fn init_aoike_sycamore_with_highlight(highlight: HighlightTheme) {
     let css_dir = Path::new("static/css");
     let sha1_file = css_dir.join("sha1");
     let needs_extraction = if css_dir.exists() && sha1_file.exists() {
@@ -57,7 +87,11 @@ pub fn init_aoike_sycamore() {
         println!("CSS assets extracted successfully");
     }
 
-    let insert_str = r#"<link rel="css" href="static/css/uno.css" data-trunk>
-<link rel="scss" href="static/css/main.scss" data-trunk>"#;
-    patch_file("index.html", insert_str, "AOIKE_SYCAMORE", Some("</head>")).unwrap();
+    let mut insert_str = r#"<link rel="css" href="static/css/uno.css" data-trunk>
+<link rel="scss" href="static/css/main.scss" data-trunk>"#
+        .to_string();
+    if let Some(path) = highlight.css_path() {
+        insert_str.push_str(&format!("\n<link rel=\"css\" href=\"{path}\" data-trunk>"));
+    }
+    patch_file("index.html", &insert_str, "AOIKE_SYCAMORE", Some("</head>")).unwrap();
 }
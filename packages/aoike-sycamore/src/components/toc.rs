@@ -0,0 +1,91 @@
+use std::{cell::RefCell, rc::Rc};
+
+use aoike::TocEntry;
+use sycamore::prelude::*;
+use wasm_bindgen::{closure::Closure, JsCast};
+
+/// A heading scrolled above this many pixels from the top of the viewport counts as read
+const SCROLL_OFFSET: f64 = 96.0;
+
+type ScrollListener = Rc<RefCell<Option<Closure<dyn Fn()>>>>;
+
+/// Sticky table of contents of a post, highlighting the heading being read
+#[component(inline_props)]
+pub fn Toc(toc: &'static [TocEntry]) -> View {
+    let active = create_signal(String::new());
+    let listener: ScrollListener = Rc::new(RefCell::new(None));
+
+    on_mount({
+        let listener = listener.clone();
+        move || {
+            let Some(window) = web_sys::window() else {
+                return;
+            };
+            let ids = flatten_ids(toc);
+            let update = move || {
+                let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+                    return;
+                };
+                let current = ids.iter().rfind(|id| {
+                    document
+                        .get_element_by_id(id)
+                        .is_some_and(|e| e.get_bounding_client_rect().top() <= SCROLL_OFFSET)
+                });
+                active.set(current.cloned().unwrap_or_default());
+            };
+            update();
+
+            let closure = Closure::<dyn Fn()>::new(update);
+            let _ = window
+                .add_event_listener_with_callback("scroll", closure.as_ref().unchecked_ref());
+            *listener.borrow_mut() = Some(closure);
+        }
+    });
+    on_cleanup(move || {
+        if let Some(closure) = listener.borrow_mut().take()
+            && let Some(window) = web_sys::window()
+        {
+            let _ = window
+                .remove_event_listener_with_callback("scroll", closure.as_ref().unchecked_ref());
+        }
+    });
+
+    view! {
+        nav(class="toc") {
+            p(class="toc-title") { "目录" }
+            (toc_list(toc, active))
+        }
+    }
+}
+
+fn toc_list(entries: &'static [TocEntry], active: Signal<String>) -> View {
+    let items = entries
+        .iter()
+        .map(|entry| {
+            let id = entry.id.as_str();
+            let class = move || if active.with(|a| a == id) { "active" } else { "" };
+            view! {
+                li {
+                    a(href=format!("#{}", entry.id), class=class) { (entry.title.clone()) }
+                    (if entry.children.is_empty() {
+                        view! {}
+                    } else {
+                        toc_list(&entry.children, active)
+                    })
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        ul { (items) }
+    }
+}
+
+/// Ids of the headings in document order
+fn flatten_ids(entries: &[TocEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .flat_map(|entry| std::iter::once(entry.id.clone()).chain(flatten_ids(&entry.children)))
+        .collect()
+}
@@ -11,6 +11,15 @@ pub fn index() -> &'static aoike::PostData {
                 .unwrap(),
             updated: aoike::time::UtcDateTime::from_unix_timestamp(1759656018i64)
                 .unwrap(),
+            tags: vec![],
+            categories: vec![],
+            draft: false,
+            uncommitted: false,
+            extra: [].into_iter().collect(),
+            toc: vec![],
+            section: vec![],
+            aliases: vec![],
+            backlinks: vec![],
         }
     });
     &INDEX
@@ -27,7 +36,7 @@ pub fn posts() -> &'static [aoike::PostData] {
             .to_string(), created :
             aoike::time::UtcDateTime::from_unix_timestamp(1759656018i64).unwrap(),
             updated : aoike::time::UtcDateTime::from_unix_timestamp(1759656018i64)
-            .unwrap(), }, aoike::PostData { title : "test".to_string(), slug : "test"
+            .unwrap(), tags : vec![], categories : vec![], draft : false, uncommitted : false, extra : [].into_iter().collect(), toc : vec![], section : vec![], aliases : vec![], backlinks : vec![], }, aoike::PostData { title : "test".to_string(), slug : "test"
             .to_string(), summary_html :
             "\n<p>Lorem ipsum dolor sit amet, consectetur adipiscing elit. Sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis...</p>"
             .to_string(), content_html :
@@ -35,7 +44,7 @@ pub fn posts() -> &'static [aoike::PostData] {
             .to_string(), created :
             aoike::time::UtcDateTime::from_unix_timestamp(1759656018i64).unwrap(),
             updated : aoike::time::UtcDateTime::from_unix_timestamp(1759656018i64)
-            .unwrap(), }, aoike::PostData { title : "test".to_string(), slug : "ce-shi"
+            .unwrap(), tags : vec![], categories : vec![], draft : false, uncommitted : false, extra : [].into_iter().collect(), toc : vec![], section : vec![], aliases : vec![], backlinks : vec![], }, aoike::PostData { title : "test".to_string(), slug : "ce-shi"
             .to_string(), summary_html :
             "\n<p>Lorem ipsum dolor sit amet, consectetur adipiscing elit. Sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis...</p>"
             .to_string(), content_html :
@@ -43,10 +52,32 @@ pub fn posts() -> &'static [aoike::PostData] {
             .to_string(), created :
             aoike::time::UtcDateTime::from_unix_timestamp(1759656018i64).unwrap(),
             updated : aoike::time::UtcDateTime::from_unix_timestamp(1759656018i64)
-            .unwrap(), }
+            .unwrap(), tags : vec![], categories : vec![], draft : false, uncommitted : false, extra : [].into_iter().collect(), toc : vec![], section : vec![], aliases : vec![], backlinks : vec![], }
         ];
-        posts.sort_by(|a, b| b.created.cmp(&a.created));
+        posts.sort_by_key(|p| std::cmp::Reverse(p.created));
         posts
     });
     &POSTS
 }
+pub fn tags() -> &'static [aoike::TaxonomyTerm] {
+    static TAGS: std::sync::LazyLock<Vec<aoike::TaxonomyTerm>> = std::sync::LazyLock::new(||
+    { vec![] });
+    &TAGS
+}
+pub fn categories() -> &'static [aoike::TaxonomyTerm] {
+    static CATEGORIES: std::sync::LazyLock<Vec<aoike::TaxonomyTerm>> = std::sync::LazyLock::new(||
+    { vec![] });
+    &CATEGORIES
+}
+pub fn sections() -> &'static [aoike::SectionData] {
+    static SECTIONS: std::sync::LazyLock<Vec<aoike::SectionData>> = std::sync::LazyLock::new(||
+    { vec![] });
+    &SECTIONS
+}
+pub fn search_index() -> &'static aoike::search::SearchIndex {
+    static SEARCH_INDEX: aoike::search::SearchIndex = aoike::search::SearchIndex {
+        docs: &[],
+        terms: &[],
+    };
+    &SEARCH_INDEX
+}
@@ -1,14 +1,22 @@
 #[cfg(feature = "build")]
 pub mod build;
+#[cfg(feature = "ssr")]
+pub mod ssg;
 
 pub mod docsgen;
 
-use aoike::PostData;
+use aoike::{
+    search::{SearchIndex, SearchResult},
+    PostData, SectionData, Site, TaxonomyTerm,
+};
 use sycamore::prelude::*;
+#[cfg(feature = "ssr")]
+use sycamore_router::StaticRouter;
 use sycamore_router::{navigate, HistoryIntegration, Route, Router};
 
 pub mod components {
     pub mod giscus;
+    pub mod toc;
 }
 
 use crate::{components::giscus::GiscusOptions, layout::base::Header};
@@ -23,8 +31,19 @@ enum AppRoutes {
     Index,
     #[to("/posts")]
     Posts,
-    #[to("/posts/<slug>")]
-    Post { slug: String },
+    /// A post or a section, like `/posts/rust/async-md` or `/posts/rust`
+    #[to("/posts/<path..>")]
+    Post { path: Vec<String> },
+    #[to("/tags")]
+    Tags,
+    #[to("/tags/<tag>")]
+    Tag { tag: String },
+    #[to("/categories")]
+    Categories,
+    #[to("/categories/<category>")]
+    Category { category: String },
+    #[to("/search")]
+    Search,
     #[not_found]
     NotFound,
 }
@@ -44,35 +63,113 @@ pub struct ConfigContext {
 }
 
 #[component(inline_props)]
-pub fn AoikeApp(config: ConfigContext, index: &'static PostData, posts: &'static [PostData]) -> View {
+pub fn AoikeApp(
+    config: ConfigContext,
+    index: &'static PostData,
+    posts: &'static [PostData],
+    tags: &'static [TaxonomyTerm],
+    categories: &'static [TaxonomyTerm],
+    sections: &'static [SectionData],
+    search_index: &'static SearchIndex,
+) -> View {
     provide_context(config);
+    let site = Site {
+        posts,
+        index,
+        tags,
+        categories,
+        sections,
+        search_index,
+    };
 
     view! {
         Router(
             integration=HistoryIntegration::new(),
-            view=move |route: ReadSignal<AppRoutes>| {
-                view! {
-                    Header()
-
-                    main(class="max-w-[80ch] w-full m-x-auto flex flex-col items-center p-8 gap-4") {
-                        (match route.get_clone() {
-                            AppRoutes::Index => view! {
-                                Index(index=index, posts=posts)
-                            },
-                            AppRoutes::Posts => view! {
-                                Posts(posts=posts)
-                            },
-                            AppRoutes::Post { slug } => view! {
-                                Post(posts=posts, slug=slug)
-                            },
-                            AppRoutes::NotFound => view! {
-                                NotFound()
-                            },
-                        })
+            view=move |route: ReadSignal<AppRoutes>| app_view(route, site.clone())
+        )
+    }
+}
+
+/// [`AoikeApp`] rendered at a fixed `path`, used for pre-rendering on the server
+#[cfg(feature = "ssr")]
+#[component(inline_props)]
+pub fn AoikeStaticApp(path: String, config: ConfigContext, site: Site) -> View {
+    provide_context(config);
+
+    view! {
+        StaticRouter(
+            route=AppRoutes::default().match_path(&path),
+            view=move |route: ReadSignal<AppRoutes>| app_view(route, site.clone())
+        )
+    }
+}
+
+/// Render `f` into `<body>`, hydrating the pre-rendered html if there is any
+#[cfg(feature = "hydrate")]
+pub fn hydrate_or_render(f: impl FnOnce() -> View + 'static) {
+    let prerendered = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.body())
+        .is_some_and(|body| body.child_element_count() > 0);
+    if prerendered {
+        sycamore::hydrate(f);
+    } else {
+        sycamore::render(f);
+    }
+}
+
+fn app_view(route: ReadSignal<AppRoutes>, site: Site) -> View {
+    let Site {
+        posts,
+        index,
+        tags,
+        categories,
+        sections,
+        search_index,
+    } = site;
+
+    view! {
+        Header()
+
+        main(class="max-w-[80ch] w-full m-x-auto flex flex-col items-center p-8 gap-4") {
+            (match route.get_clone() {
+                AppRoutes::Index => view! {
+                    Index(index=index, posts=posts)
+                },
+                AppRoutes::Posts => view! {
+                    Posts(posts=posts, sections=sections)
+                },
+                AppRoutes::Post { path } => {
+                    let slug = path.join("/");
+                    if posts.iter().any(|p| p.slug == slug) {
+                        view! { Post(posts=posts, sections=sections, slug=slug) }
+                    } else if let Some(post) = posts.iter().find(|p| p.aliases.contains(&slug)) {
+                        navigate(&format!("/posts/{}", post.slug));
+                        view! {}
+                    } else {
+                        view! { Section(posts=posts, sections=sections, slug=slug) }
                     }
                 }
-            }
-        )
+                AppRoutes::Tags => view! {
+                    Terms(title="所有标签", base="/tags", terms=tags)
+                },
+                AppRoutes::Tag { tag } => view! {
+                    TermPosts(title="标签", terms=tags, posts=posts, slug=tag)
+                },
+                AppRoutes::Categories => view! {
+                    Terms(title="所有分类", base="/categories", terms=categories)
+                },
+                AppRoutes::Category { category } => view! {
+                    TermPosts(title="分类", terms=categories, posts=posts, slug=category)
+                },
+                AppRoutes::Search => view! {
+                    Search(search_index=search_index)
+                },
+                AppRoutes::NotFound => view! {
+                    NotFound()
+                },
+            })
+        }
     }
 }
 
@@ -195,9 +292,14 @@ pub fn Hero() -> View {
 }
 
 #[component(inline_props)]
-pub fn Posts(posts: &'static [PostData]) -> View {
+pub fn Posts(posts: &'static [PostData], sections: &'static [SectionData]) -> View {
+    let top_sections = sections
+        .iter()
+        .filter(|section| !section.slug.contains('/'))
+        .collect::<Vec<_>>();
     view! {
         h1 { "所有文章" }
+        SectionList(sections=top_sections)
         (posts.iter().map(|post| {
             view! {
                 PostCard(post=post)
@@ -206,6 +308,206 @@ pub fn Posts(posts: &'static [PostData]) -> View {
     }
 }
 
+#[component(inline_props)]
+pub fn Section(
+    posts: &'static [PostData],
+    sections: &'static [SectionData],
+    slug: String,
+) -> View {
+    let Some(section) = sections.iter().find(|s| s.slug == slug) else {
+        navigate("/404");
+        return view! {};
+    };
+    let parents = section
+        .slug
+        .split('/')
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let subsections = section
+        .sections
+        .iter()
+        .filter_map(|slug| sections.iter().find(|s| &s.slug == slug))
+        .collect::<Vec<_>>();
+    let content_html = section.content_html.as_str();
+
+    view! {
+        Breadcrumbs(sections=sections, path=parents[..parents.len() - 1].to_vec())
+        (if content_html.is_empty() {
+            view! { h1 { (section.title.clone()) } }
+        } else {
+            view! {
+                div(class="markdown w-full") {
+                    div(dangerously_set_inner_html=content_html)
+                }
+            }
+        })
+        SectionList(sections=subsections)
+        (section.posts.iter().filter_map(|slug| {
+            posts.iter().find(|p| &p.slug == slug)
+        }).map(|post| {
+            view! {
+                PostCard(post=post)
+            }
+        }).collect::<Vec<_>>())
+    }
+}
+
+/// Links to `sections`, with the number of posts directly in each
+#[component(inline_props)]
+pub fn SectionList(sections: Vec<&'static SectionData>) -> View {
+    if sections.is_empty() {
+        return view! {};
+    }
+    let links = sections
+        .into_iter()
+        .map(|section| {
+            view! {
+                a(
+                    class="flex gap-1 p-x-2 p-y-1 rounded border border-slate-200 hover:border-slate-400",
+                    href=format!("/posts/{}", section.slug)
+                ) {
+                    span { "📁 " (section.title.clone()) }
+                    span(class="text-gray-400") { (section.posts.len().to_string()) }
+                }
+            }
+        })
+        .collect::<Vec<View>>();
+
+    view! {
+        div(class="w-full flex flex-wrap gap-2") {
+            (links)
+        }
+    }
+}
+
+/// `所有文章 / Rust / Async`, `path` is the slugified directory names
+#[component(inline_props)]
+pub fn Breadcrumbs(sections: &'static [SectionData], path: Vec<String>) -> View {
+    let crumbs = (1..=path.len())
+        .filter_map(|i| {
+            let slug = path[..i].join("/");
+            sections.iter().find(|s| s.slug == slug)
+        })
+        .map(|section| {
+            view! {
+                span { "/" }
+                a(class="hover:underline", href=format!("/posts/{}", section.slug)) {
+                    (section.title.clone())
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        nav(class="w-full flex gap-1 text-sm text-gray-400") {
+            a(class="hover:underline", href="/posts") { "所有文章" }
+            (crumbs)
+        }
+    }
+}
+
+#[component(inline_props)]
+pub fn Terms(title: &'static str, base: &'static str, terms: &'static [TaxonomyTerm]) -> View {
+    view! {
+        h1 { (title) }
+        div(class="w-full flex flex-wrap gap-2") {
+            (terms.iter().map(|term| {
+                view! {
+                    a(
+                        class="flex gap-1 p-x-2 p-y-1 rounded border border-slate-200 hover:border-slate-400",
+                        href=format!("{}/{}", base, term.slug)
+                    ) {
+                        span { (term.name.clone()) }
+                        span(class="text-gray-400") { (term.posts.len().to_string()) }
+                    }
+                }
+            }).collect::<Vec<_>>())
+        }
+    }
+}
+
+#[component(inline_props)]
+pub fn TermPosts(
+    title: &'static str,
+    terms: &'static [TaxonomyTerm],
+    posts: &'static [PostData],
+    slug: String,
+) -> View {
+    let Some(term) = terms.iter().find(|t| t.slug == slug) else {
+        navigate("/404");
+        return view! {};
+    };
+
+    view! {
+        h1 { (format!("{}: {}", title, term.name)) }
+        (term.posts.iter().filter_map(|slug| {
+            posts.iter().find(|p| &p.slug == slug)
+        }).map(|post| {
+            view! {
+                PostCard(post=post)
+            }
+        }).collect::<Vec<_>>())
+    }
+}
+
+#[component(inline_props)]
+pub fn Search(search_index: &'static SearchIndex) -> View {
+    let query = create_signal(String::new());
+
+    view! {
+        h1 { "搜索" }
+        input(
+            class="w-full p-2 rounded border border-slate-200 hover:border-slate-400",
+            r#type="search",
+            placeholder="搜索文章…",
+            bind:value=query,
+        )
+        (move || {
+            let query = query.get_clone();
+            if query.trim().is_empty() {
+                return view! {};
+            }
+            let results = search_index.search(&query, 20);
+            if results.is_empty() {
+                return view! {
+                    p(class="text-gray-400") { "没有找到相关的文章" }
+                };
+            }
+            results
+                .into_iter()
+                .map(|result| view! { SearchResultCard(result=result) })
+                .collect::<Vec<_>>()
+                .into()
+        })
+    }
+}
+
+#[component(inline_props)]
+pub fn SearchResultCard(result: SearchResult) -> View {
+    let snippet = result
+        .snippet
+        .into_iter()
+        .map(|(text, highlighted)| {
+            if highlighted {
+                view! { mark { (text) } }
+            } else {
+                view! { (text) }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        div(
+            class="w-full flex flex-col gap-2 p-2 rounded border border-slate-200 hover:border-slate-400"
+        ) {
+            a(href=format!("/posts/{}", result.doc.slug)) {
+                h2 { (result.doc.title) }
+            }
+            p(class="text-sm text-gray-600") { (snippet) }
+        }
+    }
+}
+
 #[component(inline_props)]
 pub fn PostCard(post: &'static PostData) -> View {
     let summary_html = post.summary_html.as_str();
@@ -231,6 +533,9 @@ pub fn PostCard(post: &'static PostData) -> View {
                         post.updated.day()
                     ))
                 }
+                (post.uncommitted.then(|| view! {
+                    span(class="text-xs text-orange-400") { "未提交" }
+                }))
             }
             div(class="summary", dangerously_set_inner_html=summary_html)
         }
@@ -238,7 +543,7 @@ pub fn PostCard(post: &'static PostData) -> View {
 }
 
 #[component(inline_props)]
-pub fn Post(posts: &'static [PostData], slug: String) -> View {
+pub fn Post(posts: &'static [PostData], sections: &'static [SectionData], slug: String) -> View {
     let config = use_context::<ConfigContext>();
 
     let Some(post) = posts.iter().find(|p| p.slug == slug) else {
@@ -248,16 +553,61 @@ pub fn Post(posts: &'static [PostData], slug: String) -> View {
 
     let content_html = post.content_html.as_str();
     view! {
+        (if post.section.is_empty() {
+            view! {}
+        } else {
+            view! { Breadcrumbs(sections=sections, path=post.section.clone()) }
+        })
+
+        (if post.toc.is_empty() {
+            view! {}
+        } else {
+            view! { components::toc::Toc(toc=&post.toc) }
+        })
+
         div(class="markdown w-full") {
             div(dangerously_set_inner_html=content_html)
         }
 
+        Backlinks(posts=posts, post=post)
+
         (config.giscus_options.clone().map(|options| {
             view! { components::giscus::Giscus(options=options) }
         }))
     }
 }
 
+/// The posts linking to `post`
+#[component(inline_props)]
+pub fn Backlinks(posts: &'static [PostData], post: &'static PostData) -> View {
+    let links = post
+        .backlinks
+        .iter()
+        .filter_map(|slug| posts.iter().find(|p| &p.slug == slug))
+        .map(|post| {
+            view! {
+                li {
+                    a(class="underline hover:underline-gray-400", href=format!("/posts/{}", post.slug)) {
+                        (post.title.clone())
+                    }
+                }
+            }
+        })
+        .collect::<Vec<View>>();
+    if links.is_empty() {
+        return view! {};
+    }
+
+    view! {
+        div(class="w-full flex flex-col gap-2 p-2 rounded border border-slate-200") {
+            h2(class="text-sm text-gray-400") { "反向链接" }
+            ul(class="flex flex-col gap-1") {
+                (links)
+            }
+        }
+    }
+}
+
 #[component]
 pub fn NotFound() -> View {
     view! {
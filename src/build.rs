@@ -1,36 +1,229 @@
+pub mod asciidoc;
+pub mod feed;
+pub mod frontmatter;
+pub mod git;
+pub mod highlight;
+pub mod html;
+pub mod ipynb;
+pub mod links;
+pub mod markdown;
+pub mod math;
+pub mod obsidian;
+pub mod org;
 pub mod post;
+pub mod report;
+pub mod search;
+pub mod section;
+pub mod sitemap;
+pub mod summary;
+pub mod taxonomy;
+pub mod toc;
+#[cfg(feature = "typst")]
+pub mod typst;
 pub mod utils;
 
+use anyhow::Context;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use relative_path::{PathExt, RelativePath};
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use time::UtcDateTime;
 
 use walkdir::WalkDir;
 
-use crate::build::post::Post;
+use crate::{
+    build::{
+        asciidoc::AsciidocPostParser,
+        git::GitTimes,
+        html::HtmlPostParser,
+        ipynb::IpynbPostParser,
+        markdown::MarkdownOptions,
+        org::OrgPostParser,
+        post::{MarkdownPostParser, Post, TypstPostParser},
+        report::BuildReport,
+        section::Section,
+        summary::SummaryStrategy,
+    },
+    MetaValue,
+};
 
 #[derive(Debug, Clone)]
 pub struct Entity {
     pub path: PathBuf,
     pub created: UtcDateTime,
     pub updated: UtcDateTime,
+    /// The file has changes not committed to git yet
+    pub uncommitted: bool,
     pub content: Vec<u8>,
+    /// Names of the directories between the posts directory and the file
+    pub section: Vec<String>,
+}
+
+/// A source of the created and updated time of a post
+///
+/// The frontmatter `date` and `updated` always take precedence, the sources are
+/// tried in order for the posts without them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateSource {
+    /// The first and the last commit of the file, following renames
+    Git,
+    /// The creation and modification time of the file
+    FileSystem,
+    /// The time of the build
+    BuildTime,
+}
+
+impl DateSource {
+    pub const DEFAULT: [DateSource; 3] = [Self::Git, Self::FileSystem, Self::BuildTime];
+}
+
+/// Options of [`parse_posts_with_options`]
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    date_sources: Vec<DateSource>,
+    summary: SummaryStrategy,
+    parsers: PostParsers,
+    strict: bool,
+    drafts: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            date_sources: DateSource::DEFAULT.to_vec(),
+            summary: SummaryStrategy::default(),
+            parsers: PostParsers::default(),
+            strict: false,
+            drafts: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// The sources of the post times, tried in order, default is [`DateSource::DEFAULT`]
+    pub fn with_date_sources(mut self, date_sources: Vec<DateSource>) -> Self {
+        self.date_sources = date_sources;
+        self
+    }
+    /// How to extract the summary of the posts without an explicit one
+    pub fn with_summary(mut self, summary: SummaryStrategy) -> Self {
+        self.summary = summary;
+        self
+    }
+    /// Markdown extensions of the `.md` posts and the markdown cells of the notebooks,
+    /// replaces the parsers registered for `md` and `ipynb`
+    pub fn with_markdown(mut self, markdown: MarkdownOptions) -> Self {
+        self.parsers = self
+            .parsers
+            .register("md", MarkdownPostParser::new(markdown))
+            .register("ipynb", IpynbPostParser::new(markdown));
+        self
+    }
+    /// Read the `.md` posts and the markdown cells of the notebooks as notes of the
+    /// Obsidian vault at `root`, with the Obsidian markdown and the attachments resolved
+    /// by their names anywhere in the vault,
+    /// replaces the parsers registered for `md` and `ipynb`
+    pub fn with_obsidian_vault(mut self, root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        let markdown = MarkdownOptions::default().with_obsidian(true);
+        self.parsers = self
+            .parsers
+            .register("md", MarkdownPostParser::new(markdown).with_vault(root))
+            .register("ipynb", IpynbPostParser::new(markdown).with_vault(root));
+        self
+    }
+    /// The root directory of the `.typ` posts, only files under it can be read by them,
+    /// default is the posts directory passed to [`parse_posts`], replaces the parser
+    /// registered for `typ`
+    pub fn with_typst_root(mut self, root: impl AsRef<Path>) -> Self {
+        self.parsers = self
+            .parsers
+            .register("typ", TypstPostParser::default().with_root(root));
+        self
+    }
+    /// The parsers of the posts by their extensions, default is [`PostParsers::default`]
+    pub fn with_parsers(mut self, parsers: PostParsers) -> Self {
+        self.parsers = parsers;
+        self
+    }
+    /// Fail the build if any post fails to parse, default is `false`, which only
+    /// reports the failed posts as warnings
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+    /// Keep the posts with `draft: true`, default is `false`, which leaves them out of
+    /// the whole site, including the taxonomies, the search index, the feeds and the
+    /// sitemap
+    pub fn with_drafts(mut self, drafts: bool) -> Self {
+        self.drafts = drafts;
+        self
+    }
 }
 
 impl Entity {
-    pub fn new(path: impl AsRef<Path>) -> Self {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
         let path = path.as_ref();
-        let content = std::fs::read(path).unwrap();
-        let created = utils::git_created_datetime(path);
-        let updated = utils::git_updated_datetime(path);
-        Self {
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        Self::resolve(path, &GitTimes::collect(dir), &DateSource::DEFAULT)
+    }
+    /// Read the file and resolve its times by trying `date_sources` in order
+    ///
+    /// Falls back to the unix epoch if none of the sources is available.
+    pub fn resolve(
+        path: impl AsRef<Path>,
+        git_times: &GitTimes,
+        date_sources: &[DateSource],
+    ) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let content =
+            std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let uncommitted = git_times.is_uncommitted(path);
+
+        let fs_times = || {
+            let metadata = std::fs::metadata(path).ok()?;
+            let updated = metadata.modified().ok()?;
+            let created = metadata.created().unwrap_or(updated);
+            Some((UtcDateTime::from(created), UtcDateTime::from(updated)))
+        };
+        let (created, mut updated) = date_sources
+            .iter()
+            .find_map(|source| match source {
+                DateSource::Git => git_times.times(path),
+                DateSource::FileSystem => fs_times(),
+                DateSource::BuildTime => {
+                    let now = UtcDateTime::now();
+                    Some((now, now))
+                }
+            })
+            .unwrap_or((UtcDateTime::UNIX_EPOCH, UtcDateTime::UNIX_EPOCH));
+        // 有未提交的修改时，最后一次提交的时间已经过时了
+        if uncommitted
+            && date_sources.contains(&DateSource::FileSystem)
+            && let Some((_, modified)) = fs_times()
+        {
+            updated = updated.max(modified);
+        }
+
+        Ok(Self {
             path: path.to_path_buf(),
             created,
             updated,
+            uncommitted,
             content,
-        }
+            section: vec![],
+        })
+    }
+    pub fn with_section(mut self, section: Vec<String>) -> Self {
+        self.section = section;
+        self
     }
     pub fn extension(&self) -> String {
         self.path
@@ -54,26 +247,259 @@ impl Entity {
 
 pub trait Parser {
     type Output;
-    fn try_parse(entity: Entity) -> Result<Self::Output, anyhow::Error>;
+    fn try_parse(&self, entity: Entity) -> Result<Self::Output, anyhow::Error>;
+}
+
+impl<F, T> Parser for F
+where
+    F: Fn(Entity) -> Result<T, anyhow::Error>,
+{
+    type Output = T;
+    fn try_parse(&self, entity: Entity) -> Result<Self::Output, anyhow::Error> {
+        self(entity)
+    }
+}
+
+/// The post parsers keyed by file extension
+///
+/// The default one parses `md` with [`MarkdownPostParser`], `typ` with
+/// [`TypstPostParser`], `ipynb` with [`IpynbPostParser`], `adoc` with
+/// [`AsciidocPostParser`], `org` with [`OrgPostParser`] and `html`/`htm` with
+/// [`HtmlPostParser`], registering a parser for an extension replaces the existing one:
+///
+/// ```ignore
+/// let parsers = PostParsers::default().register("ipynb", MyParser);
+/// let posts = parse_posts_with_options("doc-src/posts", &ParseOptions::default().with_parsers(parsers));
+/// ```
+#[derive(Clone)]
+pub struct PostParsers {
+    parsers: HashMap<String, Arc<dyn Parser<Output = Post> + Send + Sync>>,
+}
+
+impl Default for PostParsers {
+    fn default() -> Self {
+        Self::empty()
+            .register("md", MarkdownPostParser::default())
+            .register("typ", TypstPostParser::default())
+            .register("ipynb", IpynbPostParser::default())
+            .register("adoc", AsciidocPostParser)
+            .register("org", OrgPostParser)
+            .register("html", HtmlPostParser)
+            .register("htm", HtmlPostParser)
+    }
+}
+
+impl std::fmt::Debug for PostParsers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut extensions = self.extensions().collect::<Vec<_>>();
+        extensions.sort();
+        f.debug_struct("PostParsers")
+            .field("extensions", &extensions)
+            .finish()
+    }
+}
+
+impl PostParsers {
+    /// No parser registered
+    pub fn empty() -> Self {
+        Self {
+            parsers: HashMap::new(),
+        }
+    }
+    /// Parse the files with `extension` (without the leading `.`) by `parser`
+    pub fn register(
+        mut self,
+        extension: impl Into<String>,
+        parser: impl Parser<Output = Post> + Send + Sync + 'static,
+    ) -> Self {
+        self.parsers.insert(extension.into(), Arc::new(parser));
+        self
+    }
+    /// Stop parsing the files with `extension`
+    pub fn unregister(mut self, extension: &str) -> Self {
+        self.parsers.remove(extension);
+        self
+    }
+    pub fn supports(&self, extension: &str) -> bool {
+        self.parsers.contains_key(extension)
+    }
+    pub fn extensions(&self) -> impl Iterator<Item = &str> {
+        self.parsers.keys().map(|s| s.as_str())
+    }
+    /// Parse `entity` with the parser registered for its extension
+    pub fn parse(&self, entity: Entity) -> Result<Post, anyhow::Error> {
+        let extension = entity.extension();
+        let parser = self
+            .parsers
+            .get(&extension)
+            .ok_or_else(|| anyhow::anyhow!("unsupported file extension: {extension}"))?;
+        parser.try_parse(entity)
+    }
 }
 
 pub fn parse_posts(dir: impl AsRef<Path>) -> Vec<Post> {
+    parse_posts_with_options(dir, &ParseOptions::default())
+}
+
+/// Parse the posts under `dir`, emitting the errors and warnings as `cargo:warning`
+///
+/// # Panics
+///
+/// In strict mode, panics if any post fails to parse, which fails the build script.
+pub fn parse_posts_with_options(dir: impl AsRef<Path>, options: &ParseOptions) -> Vec<Post> {
+    let report = parse_posts_report(dir, options);
+    report.emit();
+    if options.strict {
+        report.into_strict().unwrap_or_else(|err| panic!("{err}"))
+    } else {
+        report.posts
+    }
+}
+
+/// Parse the posts under `dir`, collecting the per-file errors and warnings
+///
+/// Hidden files and directories like `.obsidian` are ignored. The `_index` files are
+/// skipped, they are the pages of the sections parsed by [`section::parse_sections`].
+///
+/// A post whose slug or alias is already taken by a section or another post is dropped
+/// with an error naming both, the files are walked in the order of their names so the
+/// same one is kept on every build. So is a post whose slug or alias is not a relative
+/// path, like `../x` or `/x`. The drafts are left out unless
+/// [`ParseOptions::with_drafts`] is set.
+///
+/// The relative links between the posts are then rewritten to their routes, and the
+/// broken ones are reported, see [`links::resolve_links`]. The urls of the assets are
+/// made root-absolute, see [`links::absolute_asset_urls`].
+pub fn parse_posts_report(dir: impl AsRef<Path>, options: &ParseOptions) -> BuildReport {
     let dir = dir.as_ref();
 
-    let mut posts = Vec::new();
-    for entry in WalkDir::new(dir)
+    let git_times = GitTimes::collect(dir);
+
+    let mut report = BuildReport::default();
+    // 有 `_index` 页面的分区，slug -> 目录
+    let mut sections = HashMap::new();
+    let entries = WalkDir::new(dir)
+        .sort_by_file_name()
         .into_iter()
-        .flatten()
-        .filter(|e| e.file_type().is_file())
-    {
-        let entity = Entity::new(entry.path());
-        println!("cargo:warning=building {}", entity.base_name());
-        if let Ok(post) = Post::try_from(entity) {
-            posts.push(post);
+        .filter_entry(|entry| !is_hidden(entry));
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                let path = err.path().unwrap_or(dir).to_path_buf();
+                report.error(path, &err.into());
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        if section::is_section_index(path) {
+            let slug = section::section_slug(&section::section_path(dir, path));
+            sections.insert(slug, path.parent().unwrap_or(dir).to_path_buf());
+            continue;
+        }
+        let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+        if !options.parsers.supports(extension) {
+            // 图片等其他文件是文章的附件，不需要提示
+            if TEXT_EXTENSIONS.contains(&extension.to_lowercase().as_str()) {
+                report.warning(
+                    path,
+                    format!("skipped, unsupported file extension `{extension}`"),
+                );
+            }
+            continue;
+        }
+
+        println!("cargo:warning=building {}", path.display());
+        match Entity::resolve(path, &git_times, &options.date_sources)
+            .map(|entity| entity.with_section(section::section_path(dir, path)))
+            .and_then(|entity| Post::parse(entity, options))
+        {
+            Ok(post) if post.meta.draft && !options.drafts => {}
+            Ok(post) => report.posts.push(post),
+            Err(err) => report.error(path, &err),
         }
     }
 
-    posts
+    drop_slug_collisions(&mut report, sections);
+    links::resolve_links(dir, options, &mut report);
+    links::absolute_asset_urls(dir, &mut report.posts);
+    report
+}
+
+/// Extensions of the text documents that look like posts, the unsupported ones are
+/// reported when skipped, the other files are the assets of the posts
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "text", "markdown", "mdown", "mkd", "mdx", "rmd", "qmd", "rst", "tex", "textile",
+    "wiki", "xhtml",
+];
+
+/// Hidden files and directories like `.obsidian`, except the walked directory itself
+pub(crate) fn is_hidden(entry: &walkdir::DirEntry) -> bool {
+    entry.depth() > 0 && entry.file_name().to_string_lossy().starts_with('.')
+}
+
+/// Drop the posts whose slug or aliases collide with the ones of a section or an
+/// earlier post, or would not stay under `/posts` as a route
+///
+/// `sections` are the slugs and directories of the sections with an `_index` page, the
+/// other sections are the directories of the posts.
+fn drop_slug_collisions(report: &mut BuildReport, mut sections: HashMap<String, PathBuf>) {
+    for post in &report.posts {
+        let section = &post.entity.section;
+        for i in 1..=section.len() {
+            let dir = post.path.ancestors().nth(section.len() - i + 1).unwrap();
+            sections
+                .entry(section::section_slug(&section[..i]))
+                .or_insert_with(|| dir.to_path_buf());
+        }
+    }
+
+    // slug or alias -> the file or the section directory using it
+    let mut owners: HashMap<String, PathBuf> = sections;
+    for post in std::mem::take(&mut report.posts) {
+        let slug = post.slug();
+        let aliases = post.aliases();
+        let mut slugs =
+            std::iter::once(("slug", &slug)).chain(aliases.iter().map(|a| ("alias", a)));
+        let err = slugs.find_map(|(kind, slug)| {
+            if let Some(reason) = invalid_slug(slug) {
+                return Some(anyhow::anyhow!(
+                    "{kind} `{slug}` {reason}, it must be a relative path under `/posts`"
+                ));
+            }
+            let owner = owners.get(slug)?;
+            Some(anyhow::anyhow!(
+                "{kind} `{slug}` is already used by {}, set a different `slug` in the frontmatter",
+                owner.display()
+            ))
+        });
+        if let Some(err) = err {
+            report.error(&post.path, &err);
+            continue;
+        }
+        for slug in std::iter::once(slug).chain(aliases) {
+            owners.insert(slug, post.path.clone());
+        }
+        report.posts.push(post);
+    }
+}
+
+/// Why `slug` can not be used as a route, the pages are written to the paths of it
+fn invalid_slug(slug: &str) -> Option<&'static str> {
+    if slug.starts_with('/') {
+        return Some("starts with `/`");
+    }
+    if slug.contains('\\') {
+        return Some("contains `\\`");
+    }
+    slug.split('/').find_map(|segment| match segment.trim() {
+        "" => Some("has an empty segment"),
+        "." | ".." => Some("has a `.` or `..` segment"),
+        _ => None,
+    })
 }
 
 impl ToTokens for Post {
@@ -84,9 +510,19 @@ impl ToTokens for Post {
             content_html,
             ..
         } = self;
-        let slug = self.entity.slug();
+        let slug = self.slug();
         let created = self.entity.created.unix_timestamp();
         let updated = self.entity.updated.unix_timestamp();
+        let tags = &self.meta.tags;
+        let categories = &self.meta.categories;
+        let draft = self.meta.draft;
+        let uncommitted = self.entity.uncommitted;
+        let toc = &self.toc;
+        let section = self.entity.section.iter().map(slug::slugify);
+        let aliases = self.aliases();
+        let backlinks = &self.backlinks;
+        let extra_keys = self.meta.extra.keys();
+        let extra_values = self.meta.extra.values();
         tokens.extend(quote::quote! {
             aoike::PostData {
                 title: #title.to_string(),
@@ -95,16 +531,51 @@ impl ToTokens for Post {
                 content_html: #content_html.to_string(),
                 created: aoike::time::UtcDateTime::from_unix_timestamp(#created).unwrap(),
                 updated: aoike::time::UtcDateTime::from_unix_timestamp(#updated).unwrap(),
+                tags: vec![#(#tags.to_string()),*],
+                categories: vec![#(#categories.to_string()),*],
+                draft: #draft,
+                uncommitted: #uncommitted,
+                section: vec![#(#section.to_string()),*],
+                aliases: vec![#(#aliases.to_string()),*],
+                backlinks: vec![#(#backlinks.to_string()),*],
+                extra: [#((#extra_keys.to_string(), #extra_values)),*].into_iter().collect(),
+                toc: vec![#(#toc),*],
+            }
+        });
+    }
+}
+
+impl ToTokens for MetaValue {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            MetaValue::Null => quote::quote! { aoike::MetaValue::Null },
+            MetaValue::Bool(b) => quote::quote! { aoike::MetaValue::Bool(#b) },
+            MetaValue::Integer(i) => quote::quote! { aoike::MetaValue::Integer(#i) },
+            MetaValue::Float(f) => {
+                // `to_string` keeps `NaN` and `inf` representable
+                let f = f.to_string();
+                quote::quote! { aoike::MetaValue::Float(#f.parse().unwrap()) }
+            }
+            MetaValue::String(s) => quote::quote! { aoike::MetaValue::String(#s.to_string()) },
+            MetaValue::Array(arr) => quote::quote! { aoike::MetaValue::Array(vec![#(#arr),*]) },
+            MetaValue::Table(table) => {
+                let keys = table.keys();
+                let values = table.values();
+                quote::quote! {
+                    aoike::MetaValue::Table([#((#keys.to_string(), #values)),*].into_iter().collect())
+                }
             }
         });
     }
 }
 
-pub fn get_assets_trunk_data(
-    posts: &Vec<Post>,
-    index: &Post,
-    root_dir: impl AsRef<Path>,
-) -> String {
+/// `<link>`s for trunk to copy the files referenced by the posts, to the same paths
+/// relative to `root_dir` in the output
+///
+/// The files outside `root_dir` are skipped with a `cargo:warning`, they would be
+/// copied out of the output directory.
+pub fn get_assets_trunk_data(posts: &[Post], index: &Post, root_dir: impl AsRef<Path>) -> String {
+    let root_dir = utils::normalize_path(root_dir.as_ref());
     posts
         .iter()
         .chain(std::iter::once(index))
@@ -113,25 +584,42 @@ pub fn get_assets_trunk_data(
             p.ref_paths
                 .iter()
                 .filter_map(|p| RelativePath::from_path(p).ok())
-                .map(|ref_path| {
-                    let ref_path = ref_path.to_path(file_path.parent().unwrap());
+                .filter_map(|ref_path| {
+                    // 附件可能在上级目录，如 `../attachments/a.png`
+                    let ref_path =
+                        utils::normalize_path(&ref_path.to_path(file_path.parent().unwrap()));
+                    if !ref_path.starts_with(&root_dir) {
+                        println!(
+                            "cargo:warning={}: skipped {}, it is outside {}",
+                            file_path.display(),
+                            ref_path.display(),
+                            root_dir.display()
+                        );
+                        return None;
+                    }
 
                     let relative_path = ref_path.relative_to(&root_dir).unwrap();
                     let target_path = relative_path.to_path("");
 
                     let target_dir = target_path.parent().unwrap(); //.join(&p.slug);
-                    format!(
+                    Some(format!(
                         r#"<link rel="copy-file" href="{}" data-target-path="{}" data-trunk>"#,
                         ref_path.to_string_lossy(),
                         target_dir.to_string_lossy()
-                    )
+                    ))
                 })
         })
         .collect::<Vec<_>>()
         .join("\n")
 }
 
-pub fn generate_code(posts: Vec<Post>, index: Post) -> String {
+/// `sections` are the ones with an `_index` page from [`section::parse_sections`], the
+/// other sections are derived from the posts
+pub fn generate_code(posts: Vec<Post>, index: Post, sections: Vec<Section>) -> String {
+    let tags = taxonomy::collect_tags(&posts);
+    let sections = section::collect_post_sections(&posts, &sections);
+    let categories = taxonomy::collect_categories(&posts);
+    let search_index = search::build_posts_search_index(&posts);
     let token = quote::quote! {
         pub fn index() -> &'static aoike::PostData {
             static INDEX: std::sync::LazyLock<aoike::PostData> = std::sync::LazyLock::new(|| {
@@ -142,14 +630,48 @@ pub fn generate_code(posts: Vec<Post>, index: Post) -> String {
         pub fn posts() -> &'static [aoike::PostData] {
             static POSTS: std::sync::LazyLock<Vec<aoike::PostData>> = std::sync::LazyLock::new(|| {
                 let mut posts: Vec<aoike::PostData> = vec![#(#posts),*];
-                posts.sort_by(|a, b| b.created.cmp(&a.created));
+                posts.sort_by_key(|p| std::cmp::Reverse(p.created));
                 posts
             });
             &POSTS
         }
+        pub fn tags() -> &'static [aoike::TaxonomyTerm] {
+            static TAGS: std::sync::LazyLock<Vec<aoike::TaxonomyTerm>> = std::sync::LazyLock::new(|| {
+                vec![#(#tags),*]
+            });
+            &TAGS
+        }
+        pub fn categories() -> &'static [aoike::TaxonomyTerm] {
+            static CATEGORIES: std::sync::LazyLock<Vec<aoike::TaxonomyTerm>> = std::sync::LazyLock::new(|| {
+                vec![#(#categories),*]
+            });
+            &CATEGORIES
+        }
+        pub fn sections() -> &'static [aoike::SectionData] {
+            static SECTIONS: std::sync::LazyLock<Vec<aoike::SectionData>> = std::sync::LazyLock::new(|| {
+                vec![#(#sections),*]
+            });
+            &SECTIONS
+        }
+        pub fn search_index() -> &'static aoike::search::SearchIndex {
+            static SEARCH_INDEX: aoike::search::SearchIndex = #search_index;
+            &SEARCH_INDEX
+        }
     };
 
     prettyplease::unparse(&syn::parse_quote! {
         #token
     })
 }
+
+#[cfg(test)]
+#[test]
+fn test_get_assets_trunk_data() {
+    let mut post = post::test_post("doc-src/posts/rust/a.md", "");
+    post.ref_paths = vec!["img/a.png".into(), "../../../vault/b.png".into()];
+    let index = post::test_post("doc-src/index.md", "");
+    assert_eq!(
+        get_assets_trunk_data(&[post], &index, "doc-src"),
+        r#"<link rel="copy-file" href="doc-src/posts/rust/img/a.png" data-target-path="posts/rust/img" data-trunk>"#
+    );
+}
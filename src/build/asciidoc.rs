@@ -0,0 +1,895 @@
+use std::{collections::BTreeMap, sync::LazyLock};
+
+use regex::{Captures, Regex};
+
+use crate::build::{
+    frontmatter::FrontMatter, highlight, post::Post, utils::escape_xml, Entity, Parser,
+};
+
+/// Parses AsciiDoc posts
+///
+/// Covers the commonly used subset of AsciiDoc: the document header with its
+/// attributes, sections, paragraphs, admonitions, lists, delimited blocks, tables,
+/// images, links, footnotes and the inline formatting. The header attributes are the
+/// frontmatter of the post, see [`FrontMatter::from_attributes`], and the date of the
+/// revision line is the `date` of the post.
+#[derive(Debug, Clone, Default)]
+pub struct AsciidocPostParser;
+
+impl Parser for AsciidocPostParser {
+    type Output = Post;
+    fn try_parse(&self, entity: Entity) -> Result<Self::Output, anyhow::Error> {
+        let source = std::str::from_utf8(&entity.content)?;
+        let (header, content_html) = to_html(source);
+        let meta = FrontMatter::from_attributes(
+            header
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        )?;
+        Ok(Post::from_html_entity_with_meta(content_html, entity, meta))
+    }
+}
+
+/// Convert an AsciiDoc document to html
+///
+/// Returns the attributes declared in the header and the html, the document title is
+/// the `<h1>` of the html.
+pub fn to_html(source: &str) -> (BTreeMap<String, String>, String) {
+    let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+    let lines = source.lines().collect::<Vec<_>>();
+
+    let mut converter = Converter::default();
+    let (header, title, body_start) = converter.header(&lines);
+    let mut html = String::new();
+    if let Some(title) = title {
+        html.push_str(&format!("<h1>{}</h1>\n", converter.inline(&title)));
+    }
+    html.push_str(&converter.blocks(&lines[body_start..]));
+    html.push_str(&converter.footnotes());
+    (header, html)
+}
+
+macro_rules! regex {
+    ($name:ident, $re:literal) => {
+        static $name: LazyLock<Regex> = LazyLock::new(|| Regex::new($re).unwrap());
+    };
+}
+
+regex!(ATTRIBUTE_ENTRY, r"^:(!?)([\w][\w-]*)(!?):(?:\s+(.*))?$");
+regex!(SECTION, r"^(={1,6})\s+(.+?)\s*$");
+regex!(LIST_ITEM, r"^\s*(\*{1,5}|-|\.{1,5}|\d+\.)\s+(.*)$");
+regex!(CHECKBOX, r"^\[([ xX*])\]\s+(.*)$");
+regex!(DESCRIPTION_ITEM, r"^([^\s:].*?)(::|;;)(?:\s+(.*))?$");
+regex!(
+    ADMONITION,
+    r"^(NOTE|TIP|IMPORTANT|WARNING|CAUTION):\s+(.*)$"
+);
+regex!(BLOCK_IMAGE, r"^image::([^\[\s]+)\[(.*)\]$");
+regex!(INLINE_PASS, r"\+\+\+(.+?)\+\+\+|pass:\[(.*?)\]");
+regex!(MONOSPACE, r"`\+?([^`]+?)\+?`");
+regex!(LITERAL, r"\+([^+\s](?:[^+]*?[^+\s])?)\+");
+regex!(FOOTNOTE, r"footnote:\[(.*?)\]");
+regex!(
+    XREF,
+    r"<<([^,>]+)(?:,\s*([^>]+))?>>|xref:([^\[\s]+)\[([^\]]*)\]"
+);
+regex!(INLINE_IMAGE, r"image:([^:\[\s][^\[\s]*)\[([^\]]*)\]");
+regex!(
+    LINK,
+    r"(?:link:|mailto:)([^\[\s]+)\[([^\]]*)\]|\b(https?://[^\s\[<]+)(?:\[([^\]]*)\])?"
+);
+regex!(ATTRIBUTE_REFERENCE, r"\{([\w][\w-]*)\}");
+regex!(STRONG, r"\*\*(.+?)\*\*");
+regex!(
+    STRONG_CONSTRAINED,
+    r"(^|[^\w*])\*([^\s*](?:[^*]*?[^\s*])?)\*($|[^\w*])"
+);
+regex!(EMPHASIS, r"__(.+?)__");
+regex!(
+    EMPHASIS_CONSTRAINED,
+    r"(^|[^\w_])_([^\s_](?:[^_]*?[^\s_])?)_($|[^\w_])"
+);
+regex!(MARK, r"##(.+?)##");
+regex!(
+    MARK_CONSTRAINED,
+    r"(^|[^\w#&])#([^\s#](?:[^#]*?[^\s#])?)#($|[^\w#])"
+);
+regex!(SUPERSCRIPT, r"\^([^\s^]+)\^");
+regex!(SUBSCRIPT, r"~([^\s~]+)~");
+regex!(LINE_BREAK, r"(?m) \+$");
+regex!(HOLD, "\u{1}(\\d+)\u{2}");
+
+/// Attributes of the next block, from the `[...]`, `[[id]]` and `.Title` lines before it
+#[derive(Debug, Default)]
+struct BlockAttributes {
+    positional: Vec<String>,
+    named: BTreeMap<String, String>,
+    id: Option<String>,
+    title: Option<String>,
+}
+
+impl BlockAttributes {
+    fn parse(&mut self, list: &str) {
+        for (i, item) in list.split(',').map(str::trim).enumerate() {
+            if let Some((name, value)) = item.split_once('=') {
+                let value = value.trim().trim_matches('"');
+                self.named
+                    .insert(name.trim().to_string(), value.to_string());
+                continue;
+            }
+            let item = item.trim_matches('"');
+            // `[source#id.role%option]` 的简写形式
+            let (style, id) = if i == 0 {
+                match item.split_once('#') {
+                    Some((style, rest)) => (style, rest.split(['.', '%']).next()),
+                    None => (item, None),
+                }
+            } else {
+                (item, None)
+            };
+            if let Some(id) = id.filter(|id| !id.is_empty()) {
+                self.id = Some(id.to_string());
+            }
+            for option in style.split('%').skip(1) {
+                self.named.insert(format!("{option}-option"), String::new());
+            }
+            let style = style.split(['.', '%']).next().unwrap_or_default();
+            self.positional.push(style.to_string());
+        }
+    }
+
+    fn style(&self) -> Option<&str> {
+        self.positional
+            .first()
+            .map(String::as_str)
+            .filter(|s| !s.is_empty())
+    }
+
+    fn has_option(&self, option: &str) -> bool {
+        self.named.contains_key(&format!("{option}-option"))
+            || self
+                .named
+                .get("options")
+                .or_else(|| self.named.get("opts"))
+                .is_some_and(|options| options.split(',').any(|o| o.trim() == option))
+    }
+
+    fn id_attr(&self) -> String {
+        self.id
+            .as_ref()
+            .map(|id| format!(r#" id="{}""#, escape_xml(id)))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Converter {
+    /// Document attributes for the `{name}` references
+    attributes: BTreeMap<String, String>,
+    footnotes: Vec<String>,
+}
+
+impl Converter {
+    /// Parse the header, returns the attributes in it, the title and the index of the
+    /// first line of the body
+    fn header(&mut self, lines: &[&str]) -> (BTreeMap<String, String>, Option<String>, usize) {
+        let mut header = BTreeMap::new();
+        let mut i = lines
+            .iter()
+            .position(|l| !l.trim().is_empty() && !l.starts_with("//"))
+            .unwrap_or(lines.len());
+
+        let title = lines
+            .get(i)
+            .and_then(|line| line.strip_prefix("= "))
+            .map(|title| title.trim().to_string());
+        if title.is_some() {
+            i += 1;
+        }
+
+        // 标题后依次是作者行和修订行
+        let mut implicit = 0;
+        while let Some(line) = lines.get(i).map(|l| l.trim_end()) {
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with("//") {
+            } else if let Some(caps) = ATTRIBUTE_ENTRY.captures(line) {
+                self.set_attribute(&caps);
+                let name = caps[2].to_string();
+                if caps[1].is_empty() && caps[3].is_empty() {
+                    header.insert(name, caps.get(4).map_or("", |m| m.as_str()).to_string());
+                } else {
+                    header.remove(&name);
+                }
+            } else if title.is_some() && implicit == 0 {
+                implicit += 1;
+                header.insert("author".to_string(), line.to_string());
+            } else if title.is_some() && implicit == 1 {
+                implicit += 1;
+                // `v1.0, 2024-01-02: remark`
+                let date = match line.split_once(',') {
+                    Some((_, rest)) => Some(rest.split(": ").next().unwrap_or(rest)),
+                    None if line.starts_with(|c: char| c == 'v' || c.is_ascii_digit())
+                        && !line.contains('-') =>
+                    {
+                        None
+                    }
+                    None => Some(line),
+                };
+                if let Some(date) = date {
+                    header.insert("revdate".to_string(), date.trim().to_string());
+                }
+            } else {
+                break;
+            }
+            i += 1;
+        }
+        if title.is_none() && header.is_empty() {
+            // 没有文档头，从头开始都是正文
+            return (header, None, 0);
+        }
+        (header, title, i)
+    }
+
+    fn set_attribute(&mut self, caps: &Captures) {
+        let name = caps[2].to_string();
+        if !caps[1].is_empty() || !caps[3].is_empty() {
+            self.attributes.remove(&name);
+        } else {
+            let value = caps.get(4).map_or("", |m| m.as_str()).trim().to_string();
+            self.attributes.insert(name, value);
+        }
+    }
+
+    fn blocks(&mut self, lines: &[&str]) -> String {
+        let mut out = Vec::new();
+        let mut attrs = BlockAttributes::default();
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i].trim_end();
+            if line.is_empty() {
+                i += 1;
+                continue;
+            }
+            if line.starts_with("//") && !is_delimiter(line) {
+                i += 1;
+                continue;
+            }
+            if let Some(caps) = ATTRIBUTE_ENTRY.captures(line) {
+                self.set_attribute(&caps);
+                i += 1;
+                continue;
+            }
+            if let Some(id) = line.strip_prefix("[[").and_then(|l| l.strip_suffix("]]")) {
+                attrs.id = Some(id.split(',').next().unwrap_or(id).to_string());
+                i += 1;
+                continue;
+            }
+            if let Some(list) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                attrs.parse(list);
+                i += 1;
+                continue;
+            }
+            if let Some(title) = line.strip_prefix('.')
+                && !title.starts_with(['.', ' '])
+                && !title.is_empty()
+            {
+                attrs.title = Some(title.to_string());
+                i += 1;
+                continue;
+            }
+
+            let block_attrs = std::mem::take(&mut attrs);
+            if is_delimiter(line) {
+                let (closing, lang) = match line.strip_prefix("```") {
+                    Some(lang) => ("```", Some(lang.trim()).filter(|l| !l.is_empty())),
+                    None => (line, None),
+                };
+                let end = lines[i + 1..]
+                    .iter()
+                    .position(|l| l.trim_end() == closing)
+                    .map_or(lines.len(), |p| i + 1 + p);
+                let inner = &lines[i + 1..end];
+                out.push(self.delimited(closing, lang, inner, &block_attrs));
+                i = end + 1;
+                continue;
+            }
+            if let Some(caps) = SECTION.captures(line) {
+                let level = caps[1].len();
+                out.push(format!(
+                    "<h{level}{}>{}</h{level}>",
+                    block_attrs.id_attr(),
+                    self.inline(&caps[2])
+                ));
+                i += 1;
+                continue;
+            }
+            if line == "'''" {
+                out.push("<hr>".to_string());
+                i += 1;
+                continue;
+            }
+            if line == "<<<" {
+                i += 1;
+                continue;
+            }
+            if let Some(caps) = BLOCK_IMAGE.captures(line) {
+                out.push(self.image_block(&caps[1], &caps[2], &block_attrs));
+                i += 1;
+                continue;
+            }
+            if LIST_ITEM.is_match(line) {
+                let end = list_end(lines, i, |l| LIST_ITEM.is_match(l));
+                out.push(self.list(&lines[i..end], &block_attrs));
+                i = end;
+                continue;
+            }
+            if DESCRIPTION_ITEM.is_match(line) {
+                let end = list_end(lines, i, |l| DESCRIPTION_ITEM.is_match(l));
+                out.push(self.description_list(&lines[i..end], &block_attrs));
+                i = end;
+                continue;
+            }
+
+            let end = lines[i..]
+                .iter()
+                .position(|l| l.trim().is_empty() || is_delimiter(l.trim_end()))
+                .map_or(lines.len(), |p| i + p);
+            out.push(self.paragraph(&lines[i..end], &block_attrs));
+            i = end;
+        }
+        out.join("\n")
+    }
+
+    fn delimited(
+        &mut self,
+        delimiter: &str,
+        lang: Option<&str>,
+        inner: &[&str],
+        attrs: &BlockAttributes,
+    ) -> String {
+        let id = attrs.id_attr();
+        let html = match delimiter.chars().next().unwrap_or_default() {
+            // 注释块
+            '/' => return String::new(),
+            '`' | '-' if delimiter != "--" => {
+                let lang = lang.or_else(|| match attrs.style() {
+                    Some("source") => attrs
+                        .positional
+                        .get(1)
+                        .map(String::as_str)
+                        .or_else(|| self.attributes.get("source-language").map(String::as_str)),
+                    _ => None,
+                });
+                code_block(&inner.join("\n"), lang, &id)
+            }
+            '.' => format!("<pre{id}>{}</pre>", escape_xml(&inner.join("\n"))),
+            '+' => inner.join("\n"),
+            '|' => self.table(inner, attrs),
+            '_' => {
+                let content = self.blocks(inner);
+                self.quote(&content, attrs)
+            }
+            '*' => format!(
+                r#"<aside class="sidebar"{id}>{}</aside>"#,
+                self.blocks(inner)
+            ),
+            _ => {
+                let content = self.blocks(inner);
+                match attrs.style() {
+                    Some(style) if is_admonition(style) => admonition(style, &content, &id),
+                    Some("quote") => self.quote(&content, attrs),
+                    _ if delimiter == "--" => content,
+                    _ => format!(r#"<div class="example"{id}>{content}</div>"#),
+                }
+            }
+        };
+        self.with_title(html, attrs)
+    }
+
+    fn with_title(&mut self, html: String, attrs: &BlockAttributes) -> String {
+        match &attrs.title {
+            Some(title) => format!(r#"<p class="block-title">{}</p>{html}"#, self.inline(title)),
+            None => html,
+        }
+    }
+
+    fn quote(&mut self, content: &str, attrs: &BlockAttributes) -> String {
+        let attribution = attrs
+            .positional
+            .iter()
+            .skip(1)
+            .filter(|s| !s.is_empty())
+            .map(|s| self.inline(s))
+            .collect::<Vec<_>>();
+        let footer = if attribution.is_empty() {
+            String::new()
+        } else {
+            format!("<footer>— {}</footer>", attribution.join(", "))
+        };
+        format!(
+            "<blockquote{}>{content}{footer}</blockquote>",
+            attrs.id_attr()
+        )
+    }
+
+    fn paragraph(&mut self, lines: &[&str], attrs: &BlockAttributes) -> String {
+        let id = attrs.id_attr();
+        // 缩进的段落按原样显示
+        if lines[0].starts_with([' ', '\t']) || attrs.style() == Some("literal") {
+            let indent = lines
+                .iter()
+                .map(|l| l.len() - l.trim_start().len())
+                .min()
+                .unwrap_or(0);
+            let text = lines
+                .iter()
+                .map(|l| l.get(indent..).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("\n");
+            return self.with_title(format!("<pre{id}>{}</pre>", escape_xml(&text)), attrs);
+        }
+        if attrs.style() == Some("source") {
+            let lang = attrs.positional.get(1).map(String::as_str);
+            return self.with_title(code_block(&lines.join("\n"), lang, &id), attrs);
+        }
+
+        let text = lines.join("\n");
+        let html = if let Some(style) = attrs.style().filter(|s| is_admonition(s)) {
+            let content = format!("<p>{}</p>", self.inline(&text));
+            admonition(style, &content, &id)
+        } else if let Some(caps) = ADMONITION.captures(&text) {
+            let content = format!("<p>{}</p>", self.inline(&caps[2]));
+            admonition(&caps[1], &content, &id)
+        } else if attrs.style() == Some("quote") {
+            let content = format!("<p>{}</p>", self.inline(&text));
+            self.quote(&content, attrs)
+        } else {
+            format!("<p{id}>{}</p>", self.inline(&text))
+        };
+        self.with_title(html, attrs)
+    }
+
+    fn image_block(&mut self, target: &str, attr_list: &str, attrs: &BlockAttributes) -> String {
+        let img = image_tag(target, attr_list);
+        let caption = attrs
+            .title
+            .as_ref()
+            .map(|title| format!("<figcaption>{}</figcaption>", self.inline(title)))
+            .unwrap_or_default();
+        format!("<figure{}>{img}{caption}</figure>", attrs.id_attr())
+    }
+
+    fn list(&mut self, lines: &[&str], attrs: &BlockAttributes) -> String {
+        // (marker, text)
+        let mut items: Vec<(String, String)> = Vec::new();
+        for line in lines {
+            if let Some(caps) = LIST_ITEM.captures(line) {
+                let marker = &caps[1];
+                let marker =
+                    if marker.ends_with('.') && marker.starts_with(|c: char| c.is_ascii_digit()) {
+                        "1."
+                    } else {
+                        marker
+                    };
+                items.push((marker.to_string(), caps[2].to_string()));
+            } else if let Some((_, text)) = items.last_mut()
+                && !line.trim().is_empty()
+                && line.trim() != "+"
+            {
+                text.push('\n');
+                text.push_str(line.trim());
+            }
+        }
+
+        let mut html = String::new();
+        let mut stack: Vec<(String, &str)> = Vec::new();
+        for (marker, text) in items {
+            if let Some(pos) = stack.iter().position(|(m, _)| *m == marker) {
+                while stack.len() > pos + 1 {
+                    let (_, tag) = stack.pop().unwrap();
+                    html.push_str(&format!("</li></{tag}>"));
+                }
+                html.push_str("</li>");
+            } else {
+                let tag = if marker.starts_with(['*', '-']) {
+                    "ul"
+                } else {
+                    "ol"
+                };
+                let id = if stack.is_empty() {
+                    attrs.id_attr()
+                } else {
+                    String::new()
+                };
+                html.push_str(&format!("<{tag}{id}>"));
+                stack.push((marker, tag));
+            }
+            match CHECKBOX.captures(&text) {
+                Some(caps) => {
+                    let checked = if &caps[1] == " " { "" } else { " checked" };
+                    html.push_str(&format!(
+                        r#"<li class="task-list-item"><input type="checkbox" disabled{checked}> {}"#,
+                        self.inline(&caps[2])
+                    ));
+                }
+                None => html.push_str(&format!("<li>{}", self.inline(&text))),
+            }
+        }
+        while let Some((_, tag)) = stack.pop() {
+            html.push_str(&format!("</li></{tag}>"));
+        }
+        self.with_title(html, attrs)
+    }
+
+    fn description_list(&mut self, lines: &[&str], attrs: &BlockAttributes) -> String {
+        let mut items: Vec<(String, String)> = Vec::new();
+        for line in lines {
+            if let Some(caps) = DESCRIPTION_ITEM.captures(line) {
+                let description = caps.get(3).map_or("", |m| m.as_str());
+                items.push((caps[1].to_string(), description.to_string()));
+            } else if let Some((_, description)) = items.last_mut()
+                && !line.trim().is_empty()
+            {
+                description.push('\n');
+                description.push_str(line.trim());
+            }
+        }
+        let mut html = format!("<dl{}>", attrs.id_attr());
+        for (term, description) in items {
+            html.push_str(&format!("<dt>{}</dt>", self.inline(&term)));
+            if !description.is_empty() {
+                html.push_str(&format!("<dd>{}</dd>", self.inline(&description)));
+            }
+        }
+        html.push_str("</dl>");
+        self.with_title(html, attrs)
+    }
+
+    fn table(&mut self, lines: &[&str], attrs: &BlockAttributes) -> String {
+        let first = lines.iter().position(|l| !l.trim().is_empty());
+        let mut cells: Vec<String> = Vec::new();
+        let mut first_row_len = 0;
+        for (i, line) in lines.iter().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match line.strip_prefix('|') {
+                Some(row) => {
+                    let row = row
+                        .split('|')
+                        .map(|c| c.trim().to_string())
+                        .collect::<Vec<_>>();
+                    if Some(i) == first {
+                        first_row_len = row.len();
+                    }
+                    cells.extend(row);
+                }
+                // 单元格跨行
+                None => {
+                    if let Some(cell) = cells.last_mut() {
+                        cell.push('\n');
+                        cell.push_str(line);
+                    }
+                }
+            }
+        }
+        let cols = attrs
+            .named
+            .get("cols")
+            .map(|cols| match cols.trim().parse::<usize>() {
+                Ok(n) => n,
+                Err(_) => cols.split(',').count(),
+            })
+            .unwrap_or(first_row_len)
+            .max(1);
+        // 第一行后紧跟空行时视为表头
+        let implicit_header = first.is_some_and(|i| {
+            lines.get(i + 1).is_some_and(|l| l.trim().is_empty()) && first_row_len == cols
+        });
+        let header =
+            attrs.has_option("header") || (implicit_header && !attrs.has_option("noheader"));
+
+        let mut html = format!("<table{}>", attrs.id_attr());
+        for (i, row) in cells.chunks(cols).enumerate() {
+            let tag = if header && i == 0 { "th" } else { "td" };
+            if header && i == 0 {
+                html.push_str("<thead>");
+            } else if i == usize::from(header) {
+                html.push_str("<tbody>");
+            }
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<{tag}>{}</{tag}>", self.inline(cell)));
+            }
+            html.push_str("</tr>");
+            if header && i == 0 {
+                html.push_str("</thead>");
+            }
+        }
+        if cells.len() > usize::from(header) * cols {
+            html.push_str("</tbody>");
+        }
+        html.push_str("</table>");
+        self.with_title(html, attrs)
+    }
+
+    fn footnotes(&mut self) -> String {
+        let footnotes = std::mem::take(&mut self.footnotes);
+        footnotes
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let n = i + 1;
+                format!(
+                    r#"<div class="footnote-definition" id="_footnotedef_{n}"><sup class="footnote-definition-label">{n}</sup><p>{}</p></div>"#,
+                    self.inline(text)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Apply the inline substitutions
+    fn inline(&mut self, text: &str) -> String {
+        // 先把不再参与替换的部分换成占位符，原文中的占位符字符直接去掉
+        let text = text.replace(['\u{1}', '\u{2}'], "");
+        let mut holds = Vec::new();
+        let mut hold = |html: String| {
+            holds.push(html);
+            format!("\u{1}{}\u{2}", holds.len() - 1)
+        };
+
+        let text = INLINE_PASS.replace_all(&text, |caps: &Captures| {
+            hold(caps.get(1).or(caps.get(2)).unwrap().as_str().to_string())
+        });
+        let text = MONOSPACE.replace_all(&text, |caps: &Captures| {
+            hold(format!("<code>{}</code>", escape_xml(&caps[1])))
+        });
+        let text = LITERAL.replace_all(&text, |caps: &Captures| hold(escape_xml(&caps[1])));
+        let text = FOOTNOTE.replace_all(&text, |caps: &Captures| {
+            self.footnotes.push(caps[1].to_string());
+            let n = self.footnotes.len();
+            hold(format!(
+                r##"<sup class="footnote-reference"><a href="#_footnotedef_{n}">{n}</a></sup>"##
+            ))
+        });
+        let text = XREF.replace_all(&text, |caps: &Captures| {
+            let (target, label) = match caps.get(1) {
+                Some(id) => (id.as_str(), caps.get(2).map(|m| m.as_str())),
+                None => (&caps[3], caps.get(4).map(|m| m.as_str())),
+            };
+            let href = if target.contains(['.', '/']) {
+                target.to_string()
+            } else {
+                format!("#{target}")
+            };
+            let label = label.filter(|l| !l.is_empty()).unwrap_or(target);
+            hold(format!(
+                r#"<a href="{}">{}</a>"#,
+                escape_xml(&href),
+                quotes(&escape_xml(label))
+            ))
+        });
+        let text =
+            INLINE_IMAGE.replace_all(&text, |caps: &Captures| hold(image_tag(&caps[1], &caps[2])));
+        let text = LINK.replace_all(&text, |caps: &Captures| {
+            let (target, label) = match caps.get(1) {
+                Some(target) => {
+                    let mailto = caps[0].starts_with("mailto:");
+                    let target = if mailto {
+                        format!("mailto:{}", target.as_str())
+                    } else {
+                        target.as_str().to_string()
+                    };
+                    (target, caps.get(2).map(|m| m.as_str()))
+                }
+                None => (caps[3].to_string(), caps.get(4).map(|m| m.as_str())),
+            };
+            let label = label
+                .filter(|l| !l.is_empty())
+                .map(|l| quotes(&escape_xml(l)))
+                .unwrap_or_else(|| escape_xml(target.trim_start_matches("mailto:")));
+            hold(format!(r#"<a href="{}">{label}</a>"#, escape_xml(&target)))
+        });
+
+        let text = escape_xml(&text);
+        let text = ATTRIBUTE_REFERENCE.replace_all(&text, |caps: &Captures| {
+            match self.attributes.get(&caps[1]) {
+                Some(value) => escape_xml(value),
+                None => caps[0].to_string(),
+            }
+        });
+        let text = quotes(&text);
+        let text = LINE_BREAK.replace_all(&text, "<br>");
+
+        HOLD.replace_all(&text, |caps: &Captures| {
+            caps[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| holds.get(i))
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+    }
+}
+
+/// Strong, emphasis, mark, superscript and subscript
+fn quotes(text: &str) -> String {
+    let mut text = text.to_string();
+    for (unconstrained, constrained, tag) in [
+        (&STRONG, &STRONG_CONSTRAINED, "strong"),
+        (&EMPHASIS, &EMPHASIS_CONSTRAINED, "em"),
+        (&MARK, &MARK_CONSTRAINED, "mark"),
+    ] {
+        text = unconstrained
+            .replace_all(&text, format!("<{tag}>$1</{tag}>"))
+            .into_owned();
+        // 相邻的两处共用中间的分隔字符，所以替换两遍
+        for _ in 0..2 {
+            text = constrained
+                .replace_all(&text, format!("${{1}}<{tag}>${{2}}</{tag}>${{3}}"))
+                .into_owned();
+        }
+    }
+    let text = SUPERSCRIPT.replace_all(&text, "<sup>$1</sup>");
+    SUBSCRIPT.replace_all(&text, "<sub>$1</sub>").into_owned()
+}
+
+fn is_delimiter(line: &str) -> bool {
+    if line == "--" || line == "|===" || line.starts_with("```") {
+        return true;
+    }
+    let mut chars = line.chars();
+    let Some(c) = chars.next() else {
+        return false;
+    };
+    "-.+_=*/".contains(c) && line.len() >= 4 && chars.all(|x| x == c)
+}
+
+fn is_admonition(style: &str) -> bool {
+    matches!(style, "NOTE" | "TIP" | "IMPORTANT" | "WARNING" | "CAUTION")
+}
+
+fn admonition(style: &str, content: &str, id: &str) -> String {
+    let class = style.to_lowercase();
+    format!(
+        r#"<div class="admonition {class}"{id}><p class="admonition-title">{style}</p>{content}</div>"#
+    )
+}
+
+fn code_block(code: &str, lang: Option<&str>, id: &str) -> String {
+    match lang {
+        Some(lang) => {
+            let html = highlight::highlight_code(code, lang).unwrap_or_else(|| escape_xml(code));
+            format!(
+                r#"<pre{id}><code class="language-{}">{html}</code></pre>"#,
+                escape_xml(lang)
+            )
+        }
+        None => format!("<pre{id}><code>{}</code></pre>", escape_xml(code)),
+    }
+}
+
+fn image_tag(target: &str, attr_list: &str) -> String {
+    let mut attrs = BlockAttributes::default();
+    attrs.parse(attr_list);
+    let alt = attrs
+        .style()
+        .map(str::to_string)
+        .unwrap_or_else(|| target.rsplit('/').next().unwrap_or(target).to_string());
+    let mut img = format!(
+        r#"<img src="{}" alt="{}""#,
+        escape_xml(target),
+        escape_xml(&alt)
+    );
+    for (i, name) in [(1, "width"), (2, "height")] {
+        let value = attrs.named.get(name).or(attrs.positional.get(i));
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            img.push_str(&format!(r#" {name}="{}""#, escape_xml(value)));
+        }
+    }
+    img.push('>');
+    img
+}
+
+/// The end of a list starting at `start`, blank lines between the items are allowed
+fn list_end(lines: &[&str], start: usize, is_item: impl Fn(&str) -> bool) -> usize {
+    let mut i = start + 1;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        if line.is_empty() {
+            let next = lines[i..].iter().position(|l| !l.trim().is_empty());
+            match next {
+                Some(p) if is_item(lines[i + p]) => i += p,
+                _ => return i,
+            }
+        } else if is_delimiter(line)
+            || line.starts_with("//")
+            || (line.starts_with('[') && line.ends_with(']'))
+        {
+            // 注释行用来分开相邻的两个列表
+            return i;
+        } else {
+            i += 1;
+        }
+    }
+    i
+}
+
+#[cfg(test)]
+#[test]
+fn test_asciidoc_to_html() {
+    let source = r#"= Hello *AsciiDoc*
+Jane Doe <jane@example.com>
+v1.0, 2024-01-02: first draft
+:tags: rust, notes
+:description: A short post
+:product: aoike
+
+Welcome to {product}, see https://example.com[the site] and <<usage>>.footnote:[A footnote.]
+
+[[usage]]
+== Usage
+
+NOTE: Read the *docs* first.
+
+* one `code`
+** nested
+* [x] done
+
+//-
+. first
+. second
+
+[source,rust]
+.main.rs
+----
+fn main() {}
+----
+
+|===
+|Name |Value
+
+|a |1
+|===
+
+image::assets/avatar.jpg[Avatar, 200]
+"#;
+    let (header, html) = to_html(source);
+    assert_eq!(header["revdate"], "2024-01-02");
+    assert_eq!(header["author"], "Jane Doe <jane@example.com>");
+    assert_eq!(header["tags"], "rust, notes");
+
+    assert!(html.starts_with("<h1>Hello <strong>AsciiDoc</strong></h1>"));
+    assert!(html.contains(r##"Welcome to aoike, see <a href="https://example.com">the site</a> and <a href="#usage">usage</a>."##));
+    assert!(
+        html.contains(r##"<sup class="footnote-reference"><a href="#_footnotedef_1">1</a></sup>"##)
+    );
+    assert!(html.contains(r#"<h2 id="usage">Usage</h2>"#));
+    assert!(html.contains(r#"<div class="admonition note"><p class="admonition-title">NOTE</p><p>Read the <strong>docs</strong> first.</p></div>"#));
+    assert!(html.contains("<ul><li>one <code>code</code><ul><li>nested</li></ul></li><li class=\"task-list-item\"><input type=\"checkbox\" disabled checked> done</li></ul>"));
+    assert!(html.contains("<ol><li>first</li><li>second</li></ol>"));
+    assert!(html.contains(r#"<p class="block-title">main.rs</p><pre><code class="language-rust"><span class="hl-source hl-rust">"#));
+    assert!(html.contains("<table><thead><tr><th>Name</th><th>Value</th></tr></thead><tbody><tr><td>a</td><td>1</td></tr></tbody></table>"));
+    assert!(
+        html.contains(r#"<figure><img src="assets/avatar.jpg" alt="Avatar" width="200"></figure>"#)
+    );
+    assert!(html.contains(r#"<div class="footnote-definition" id="_footnotedef_1">"#));
+
+    let meta =
+        FrontMatter::from_attributes(header.iter().map(|(k, v)| (k.as_str(), v.as_str()))).unwrap();
+    assert_eq!(meta.date.unwrap().unix_timestamp(), 1704153600);
+    assert_eq!(meta.tags, vec!["rust", "notes"]);
+    assert_eq!(meta.summary.as_deref(), Some("A short post"));
+}
+
+#[cfg(test)]
+#[test]
+fn test_placeholder_in_source() {
+    let (_, html) = to_html("a \u{1}7\u{2} `b` \u{1}99999999999999999999\u{2}\n");
+    assert_eq!(html, "<p>a 7 <code>b</code> 99999999999999999999</p>");
+}
@@ -0,0 +1,320 @@
+use std::path::{Path, PathBuf};
+
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+
+use crate::build::{links, post::Post, utils};
+
+/// Site metadata used by the feeds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedConfig {
+    title: String,
+    base_url: String,
+    author: Option<String>,
+    description: Option<String>,
+    limit: Option<usize>,
+    full_content: bool,
+}
+
+impl FeedConfig {
+    /// `base_url` is the url the site is deployed to, like `https://example.com`
+    pub fn new(title: String, base_url: String) -> Self {
+        Self {
+            title,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            author: None,
+            description: None,
+            limit: None,
+            full_content: true,
+        }
+    }
+    pub fn with_author(mut self, author: String) -> Self {
+        self.author = Some(author);
+        self
+    }
+    pub fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+    /// Only include the newest `limit` posts
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+    /// Whether to include `content_html` besides `summary_html`, default is `true`
+    pub fn with_full_content(mut self, full_content: bool) -> Self {
+        self.full_content = full_content;
+        self
+    }
+
+    fn post_url(&self, post: &Post) -> String {
+        format!("{}/posts/{}", self.base_url, post.slug())
+    }
+
+    /// `html` of `post` with absolute urls, feed readers show it out of the site
+    fn absolute_html(&self, html: &str, post: &Post) -> String {
+        let page = format!("/posts/{}", post.slug());
+        links::map_urls(html, |url| {
+            let path = match url.starts_with('/') && !url.starts_with("//") {
+                true => url.to_string(),
+                false => links::resolve_url(url, &page)?,
+            };
+            Some(format!("{}{path}", self.base_url))
+        })
+    }
+
+    /// The posts newest first, drafts are already left out by [`crate::build::ParseOptions`]
+    fn posts<'a>(&self, posts: &'a [Post]) -> Vec<&'a Post> {
+        let mut posts = posts.iter().collect::<Vec<_>>();
+        posts.sort_by_key(|p| std::cmp::Reverse(p.created));
+        posts.truncate(self.limit.unwrap_or(usize::MAX));
+        posts
+    }
+}
+
+/// Generate an RSS 2.0 feed
+pub fn rss(posts: &[Post], config: &FeedConfig) -> String {
+    let esc = utils::escape_xml;
+    let posts = config.posts(posts);
+
+    let mut out = String::new();
+    out.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    out.push('\n');
+    out.push_str(concat!(
+        r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom""#,
+        r#" xmlns:content="http://purl.org/rss/1.0/modules/content/""#,
+        r#" xmlns:dc="http://purl.org/dc/elements/1.1/">"#
+    ));
+    out.push_str("\n<channel>\n");
+    out.push_str(&format!("<title>{}</title>\n", esc(&config.title)));
+    out.push_str(&format!("<link>{}/</link>\n", esc(&config.base_url)));
+    out.push_str(&format!(
+        "<description>{}</description>\n",
+        esc(config.description.as_deref().unwrap_or(&config.title))
+    ));
+    out.push_str(&format!(
+        r#"<atom:link href="{}/feed.xml" rel="self" type="application/rss+xml"/>"#,
+        esc(&config.base_url)
+    ));
+    out.push('\n');
+    if let Some(post) = posts.iter().max_by_key(|p| p.updated) {
+        out.push_str(&format!(
+            "<lastBuildDate>{}</lastBuildDate>\n",
+            post.updated.format(&Rfc2822).unwrap()
+        ));
+    }
+
+    for post in posts {
+        let url = config.post_url(post);
+        out.push_str("<item>\n");
+        out.push_str(&format!("<title>{}</title>\n", esc(&post.title)));
+        out.push_str(&format!("<link>{}</link>\n", esc(&url)));
+        out.push_str(&format!(r#"<guid isPermaLink="true">{}</guid>"#, esc(&url)));
+        out.push('\n');
+        out.push_str(&format!(
+            "<pubDate>{}</pubDate>\n",
+            post.created.format(&Rfc2822).unwrap()
+        ));
+        if let Some(author) = &config.author {
+            out.push_str(&format!("<dc:creator>{}</dc:creator>\n", esc(author)));
+        }
+        for category in post.meta.categories.iter().chain(&post.meta.tags) {
+            out.push_str(&format!("<category>{}</category>\n", esc(category)));
+        }
+        out.push_str(&format!(
+            "<description>{}</description>\n",
+            esc(&config.absolute_html(&post.summary_html, post))
+        ));
+        if config.full_content {
+            out.push_str(&format!(
+                "<content:encoded>{}</content:encoded>\n",
+                esc(&config.absolute_html(&post.content_html, post))
+            ));
+        }
+        out.push_str("</item>\n");
+    }
+
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
+
+/// Generate an Atom 1.0 feed
+pub fn atom(posts: &[Post], config: &FeedConfig) -> String {
+    let esc = utils::escape_xml;
+    let posts = config.posts(posts);
+    let updated = posts
+        .iter()
+        .map(|p| p.updated)
+        .max()
+        .unwrap_or(time::UtcDateTime::UNIX_EPOCH);
+
+    let mut out = String::new();
+    out.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    out.push('\n');
+    out.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    out.push('\n');
+    out.push_str(&format!("<title>{}</title>\n", esc(&config.title)));
+    if let Some(description) = &config.description {
+        out.push_str(&format!("<subtitle>{}</subtitle>\n", esc(description)));
+    }
+    out.push_str(&format!(
+        r#"<link href="{}/atom.xml" rel="self" type="application/atom+xml"/>"#,
+        esc(&config.base_url)
+    ));
+    out.push('\n');
+    out.push_str(&format!(r#"<link href="{}/"/>"#, esc(&config.base_url)));
+    out.push('\n');
+    out.push_str(&format!("<id>{}/</id>\n", esc(&config.base_url)));
+    out.push_str(&format!(
+        "<updated>{}</updated>\n",
+        updated.format(&Rfc3339).unwrap()
+    ));
+    if let Some(author) = &config.author {
+        out.push_str(&format!("<author><name>{}</name></author>\n", esc(author)));
+    }
+
+    for post in posts {
+        let url = config.post_url(post);
+        out.push_str("<entry>\n");
+        out.push_str(&format!("<title>{}</title>\n", esc(&post.title)));
+        out.push_str(&format!(r#"<link href="{}"/>"#, esc(&url)));
+        out.push('\n');
+        out.push_str(&format!("<id>{}</id>\n", esc(&url)));
+        out.push_str(&format!(
+            "<published>{}</published>\n",
+            post.created.format(&Rfc3339).unwrap()
+        ));
+        out.push_str(&format!(
+            "<updated>{}</updated>\n",
+            post.updated.format(&Rfc3339).unwrap()
+        ));
+        for category in post.meta.categories.iter().chain(&post.meta.tags) {
+            out.push_str(&format!(r#"<category term="{}"/>"#, esc(category)));
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "<summary type=\"html\">{}</summary>\n",
+            esc(&config.absolute_html(&post.summary_html, post))
+        ));
+        if config.full_content {
+            out.push_str(&format!(
+                "<content type=\"html\">{}</content>\n",
+                esc(&config.absolute_html(&post.content_html, post))
+            ));
+        }
+        out.push_str("</entry>\n");
+    }
+
+    out.push_str("</feed>\n");
+    out
+}
+
+/// Write `feed.xml` (RSS) and `atom.xml` (Atom) into `dir`
+///
+/// Files are only rewritten when their content changes.
+pub fn write_feeds(
+    posts: &[Post],
+    config: &FeedConfig,
+    dir: impl AsRef<Path>,
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let mut paths = Vec::new();
+    for (name, content) in [
+        ("feed.xml", rss(posts, config)),
+        ("atom.xml", atom(posts, config)),
+    ] {
+        let path = dir.join(name);
+        utils::write_if_changed(&path, &content)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// `<link>`s for trunk to copy the feeds written by [`write_feeds`] to the root of the
+/// output, and for readers to discover them
+pub fn get_feeds_trunk_data(config: &FeedConfig, dir: impl AsRef<Path>) -> String {
+    let dir = dir.as_ref();
+    let title = utils::escape_xml(&config.title);
+    [
+        format!(
+            r#"<link rel="copy-file" href="{}" data-trunk>"#,
+            dir.join("feed.xml").to_string_lossy()
+        ),
+        format!(
+            r#"<link rel="copy-file" href="{}" data-trunk>"#,
+            dir.join("atom.xml").to_string_lossy()
+        ),
+        format!(
+            r#"<link rel="alternate" type="application/rss+xml" title="{title}" href="/feed.xml">"#
+        ),
+        format!(
+            r#"<link rel="alternate" type="application/atom+xml" title="{title}" href="/atom.xml">"#
+        ),
+    ]
+    .join("\n")
+}
+
+#[cfg(test)]
+fn test_posts() -> Vec<Post> {
+    use crate::build::post::test_post;
+
+    let mut old = test_post("old.md", "<h1>Old</h1>\n<p>old</p>\n");
+    old.entity.created = time::UtcDateTime::from_unix_timestamp(1704153600).unwrap();
+    old.entity.updated = old.entity.created;
+    let mut new = test_post(
+        "rust/new.md",
+        r##"<h1>New &amp; Shiny</h1>
+<p><img src="./assets/a.png" srcset="a.png 1x, ../b.png 2x"> <a href="#usage">usage</a> <a href="/posts/old-md">old</a> <a href="https://example.org/x">remote</a></p>
+<p><a href='../old.md'>raw</a> <video src=clip.mp4></video></p>
+"##,
+    );
+    new.entity.section = vec!["rust".to_string()];
+    new.entity.created = time::UtcDateTime::from_unix_timestamp(1704240000).unwrap();
+    new.entity.updated = new.entity.created;
+    new.meta.tags = vec!["rust".to_string()];
+    vec![old, new]
+}
+
+#[cfg(test)]
+#[test]
+fn test_rss() {
+    let config = FeedConfig::new("Blog".to_string(), "https://example.com/".to_string())
+        .with_author("Me".to_string())
+        .with_limit(1);
+    let rss = rss(&test_posts(), &config);
+    assert!(rss.contains("<link>https://example.com/</link>"));
+    assert!(rss.contains("<lastBuildDate>Wed, 03 Jan 2024 00:00:00 +0000</lastBuildDate>"));
+    assert_eq!(rss.matches("<item>").count(), 1);
+    assert!(rss.contains("<title>New &amp; Shiny</title>"));
+    assert!(rss.contains("<link>https://example.com/posts/rust/new-md</link>"));
+    assert!(rss.contains("<dc:creator>Me</dc:creator>"));
+    assert!(rss.contains("<category>rust</category>"));
+    assert!(rss.contains(concat!(
+        r#"&lt;img src=&quot;https://example.com/posts/rust/assets/a.png&quot;"#,
+        r#" srcset=&quot;https://example.com/posts/rust/a.png 1x, https://example.com/posts/b.png 2x&quot;&gt;"#,
+    )));
+    assert!(rss.contains(
+        r#"&lt;a href=&quot;https://example.com/posts/rust/new-md#usage&quot;&gt;usage&lt;/a&gt;"#
+    ));
+    assert!(rss.contains(r#"href=&quot;https://example.com/posts/old-md&quot;"#));
+    assert!(rss.contains(r#"href=&quot;https://example.org/x&quot;"#));
+    assert!(rss.contains(r#"&lt;a href=&quot;https://example.com/posts/old.md&quot;&gt;raw"#));
+    assert!(rss.contains(r#"src=&quot;https://example.com/posts/rust/clip.mp4&quot;"#));
+}
+
+#[cfg(test)]
+#[test]
+fn test_atom() {
+    let config = FeedConfig::new("Blog".to_string(), "https://example.com".to_string())
+        .with_full_content(false);
+    let atom = atom(&test_posts(), &config);
+    assert!(atom.contains("<updated>2024-01-03T00:00:00Z</updated>\n<entry>"));
+    let entries = atom.split("<entry>").skip(1).collect::<Vec<_>>();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].contains(r#"<link href="https://example.com/posts/rust/new-md"/>"#));
+    assert!(entries[1].contains("<id>https://example.com/posts/old-md</id>"));
+    assert!(entries[1].contains("<published>2024-01-02T00:00:00Z</published>"));
+    assert!(entries[0].contains(r#"src=&quot;https://example.com/posts/rust/assets/a.png&quot;"#));
+    assert!(!atom.contains("<content"));
+}
@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use time::{
+    format_description::well_known::{Rfc2822, Rfc3339},
+    Date, OffsetDateTime, PrimitiveDateTime, Time, UtcDateTime,
+};
+
+use crate::MetaValue;
+
+/// Metadata declared in a `---` YAML or `+++` TOML block at the top of a post
+///
+/// Every field that is set here overrides the value derived from the content
+/// (title from `<h1>`, summary from the text) or from git (dates).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    pub title: Option<String>,
+    pub date: Option<UtcDateTime>,
+    pub updated: Option<UtcDateTime>,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    pub draft: bool,
+    pub summary: Option<String>,
+    pub slug: Option<String>,
+    /// Former slugs of the post, redirected to it, see [`Post::aliases`](crate::build::post::Post::aliases)
+    pub aliases: Vec<String>,
+    pub extra: BTreeMap<String, MetaValue>,
+}
+
+impl FrontMatter {
+    pub fn from_yaml(source: &str) -> Result<Self, anyhow::Error> {
+        let value: serde_yaml::Value =
+            serde_yaml::from_str(source).context("invalid yaml frontmatter")?;
+        match yaml_to_meta(value) {
+            MetaValue::Table(table) => Self::from_table(table),
+            MetaValue::Null => Ok(Self::default()),
+            _ => anyhow::bail!("yaml frontmatter is not a mapping"),
+        }
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, anyhow::Error> {
+        let table: toml::Table = toml::from_str(source).context("invalid toml frontmatter")?;
+        match toml_to_meta(toml::Value::Table(table)) {
+            MetaValue::Table(table) => Self::from_table(table),
+            _ => unreachable!(),
+        }
+    }
+
+    /// From the string attributes of a document header, like the `:name: value` of
+    /// AsciiDoc or the `#+NAME: value` of Org
+    ///
+    /// Names are case-insensitive, `revdate` is an alias of `date`, `keywords` and
+    /// `filetags` of `tags`, and `description` of `summary`. Lists are separated by `,`
+    /// or `:` (only `,` for `aliases`), and the weekdays and brackets of Org timestamps are ignored in dates.
+    pub fn from_attributes<'a>(
+        attributes: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, anyhow::Error> {
+        let mut table = BTreeMap::new();
+        for (name, value) in attributes {
+            let name = name.to_lowercase();
+            let value = value.trim();
+            let (key, value) = match name.as_str() {
+                "date" | "revdate" | "updated" => {
+                    let key = if name == "updated" { "updated" } else { "date" };
+                    let raw = value.trim_matches(['<', '>', '[', ']']);
+                    // `<2024-01-02 Tue 10:00>` -> `2024-01-02 10:00`
+                    let date = match parse_datetime(raw) {
+                        Some(_) => raw.to_string(),
+                        None => raw
+                            .split_whitespace()
+                            .filter(|token| token.contains(|c: char| c.is_ascii_digit()))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    };
+                    if parse_datetime(&date).is_none() {
+                        anyhow::bail!(
+                            "unsupported date format in `{name}`: {value}, use `YYYY-MM-DD`, RFC 3339 or RFC 2822"
+                        );
+                    }
+                    (key, MetaValue::String(date))
+                }
+                "tags" | "keywords" | "filetags" | "categories" => {
+                    let key = if name == "categories" {
+                        "categories"
+                    } else {
+                        "tags"
+                    };
+                    let values = value
+                        .split([',', ':'])
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(|s| MetaValue::String(s.to_string()))
+                        .collect();
+                    (key, MetaValue::Array(values))
+                }
+                "aliases" => {
+                    let values = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(|s| MetaValue::String(s.to_string()))
+                        .collect();
+                    ("aliases", MetaValue::Array(values))
+                }
+                "draft" => {
+                    let draft = matches!(value.to_lowercase().as_str(), "" | "true" | "yes" | "t");
+                    ("draft", MetaValue::Bool(draft))
+                }
+                "description" | "summary" => ("summary", MetaValue::String(value.to_string())),
+                _ => (name.as_str(), MetaValue::String(value.to_string())),
+            };
+            table.insert(key.to_string(), value);
+        }
+        Self::from_table(table)
+    }
+
+    pub fn from_table(mut table: BTreeMap<String, MetaValue>) -> Result<Self, anyhow::Error> {
+        let mut take_string = |key: &str| -> Result<Option<String>, anyhow::Error> {
+            match table.remove(key) {
+                None | Some(MetaValue::Null) => Ok(None),
+                Some(MetaValue::String(s)) => Ok(Some(s)),
+                Some(v) => anyhow::bail!("frontmatter `{key}` should be a string, got {v:?}"),
+            }
+        };
+
+        let title = take_string("title")?;
+        let summary = take_string("summary")?;
+        let slug = take_string("slug")?;
+        let date = take_string("date")?
+            .map(|s| parse_datetime(&s).with_context(|| format!("invalid `date`: {s}")))
+            .transpose()?;
+        let updated = take_string("updated")?
+            .map(|s| parse_datetime(&s).with_context(|| format!("invalid `updated`: {s}")))
+            .transpose()?;
+
+        let mut take_strings = |key: &str| -> Result<Vec<String>, anyhow::Error> {
+            match table.remove(key) {
+                None | Some(MetaValue::Null) => Ok(vec![]),
+                Some(MetaValue::String(s)) => Ok(vec![s]),
+                Some(MetaValue::Array(values)) => values
+                    .into_iter()
+                    .map(|value| match value {
+                        MetaValue::String(s) => Ok(s),
+                        v => anyhow::bail!(
+                            "frontmatter `{key}` should only contain strings, got {v:?}"
+                        ),
+                    })
+                    .collect(),
+                Some(v) => anyhow::bail!("frontmatter `{key}` should be an array, got {v:?}"),
+            }
+        };
+        let tags = take_strings("tags")?;
+        let categories = take_strings("categories")?;
+        let aliases = take_strings("aliases")?;
+
+        let draft = match table.remove("draft") {
+            None | Some(MetaValue::Null) => false,
+            Some(MetaValue::Bool(b)) => b,
+            Some(v) => anyhow::bail!("frontmatter `draft` should be a bool, got {v:?}"),
+        };
+
+        Ok(Self {
+            title,
+            date,
+            updated,
+            tags,
+            categories,
+            draft,
+            summary,
+            slug,
+            aliases,
+            extra: table,
+        })
+    }
+}
+
+/// Split the frontmatter block from the content
+///
+/// Returns: (frontmatter, rest_of_content)
+pub fn split_frontmatter(content: &str) -> Result<(FrontMatter, &str), anyhow::Error> {
+    let source = content.strip_prefix('\u{feff}').unwrap_or(content);
+
+    for (delimiter, parse) in [
+        ("---", FrontMatter::from_yaml as fn(&str) -> _),
+        ("+++", FrontMatter::from_toml as fn(&str) -> _),
+    ] {
+        let Some(rest) = strip_delimiter_line(source, delimiter) else {
+            continue;
+        };
+        let mut offset = 0;
+        for line in rest.split_inclusive('\n') {
+            if line.trim_end() == delimiter {
+                let frontmatter = parse(&rest[..offset])?;
+                return Ok((frontmatter, &rest[offset + line.len()..]));
+            }
+            offset += line.len();
+        }
+    }
+
+    Ok((FrontMatter::default(), source))
+}
+
+fn strip_delimiter_line<'a>(source: &'a str, delimiter: &str) -> Option<&'a str> {
+    let rest = source.strip_prefix(delimiter)?;
+    let (line, rest) = rest.split_once('\n').unwrap_or((rest, ""));
+    line.trim().is_empty().then_some(rest)
+}
+
+/// Parse `2024-01-02`, `2024-01-02 03:04[:05]`, `2024-01-02T03:04:05`, rfc3339 or rfc2822
+pub fn parse_datetime(s: &str) -> Option<UtcDateTime> {
+    let s = s.trim();
+    if let Ok(datetime) = OffsetDateTime::parse(s, &Rfc3339) {
+        return Some(datetime.to_utc());
+    }
+    if let Ok(datetime) = OffsetDateTime::parse(s, &Rfc2822) {
+        return Some(datetime.to_utc());
+    }
+
+    let (date, time) = s
+        .split_once(['T', ' '])
+        .map(|(date, time)| (date, Some(time)))
+        .unwrap_or((s, None));
+
+    let mut date_parts = date.splitn(3, '-').map(str::parse::<i32>);
+    let year = date_parts.next()?.ok()?;
+    let month = u8::try_from(date_parts.next()?.ok()?).ok()?;
+    let day = u8::try_from(date_parts.next()?.ok()?).ok()?;
+    let date = Date::from_calendar_date(year, month.try_into().ok()?, day).ok()?;
+
+    let time = match time {
+        None => Time::MIDNIGHT,
+        Some(time) => {
+            let mut time_parts = time.splitn(3, ':').map(str::parse::<u8>);
+            let hour = time_parts.next()?.ok()?;
+            let minute = time_parts.next()?.ok()?;
+            let second = time_parts.next().transpose().ok()?.unwrap_or(0);
+            Time::from_hms(hour, minute, second).ok()?
+        }
+    };
+
+    Some(PrimitiveDateTime::new(date, time).as_utc())
+}
+
+fn yaml_to_meta(value: serde_yaml::Value) -> MetaValue {
+    use serde_yaml::Value;
+    match value {
+        Value::Null => MetaValue::Null,
+        Value::Bool(b) => MetaValue::Bool(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => MetaValue::Integer(i),
+            None => MetaValue::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => MetaValue::String(s),
+        Value::Sequence(seq) => MetaValue::Array(seq.into_iter().map(yaml_to_meta).collect()),
+        Value::Mapping(map) => MetaValue::Table(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let key = match k {
+                        Value::String(s) => s,
+                        k => serde_yaml::to_string(&k)
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string(),
+                    };
+                    (key, yaml_to_meta(v))
+                })
+                .collect(),
+        ),
+        Value::Tagged(tagged) => yaml_to_meta(tagged.value),
+    }
+}
+
+fn toml_to_meta(value: toml::Value) -> MetaValue {
+    use toml::Value;
+    match value {
+        Value::String(s) => MetaValue::String(s),
+        Value::Integer(i) => MetaValue::Integer(i),
+        Value::Float(f) => MetaValue::Float(f),
+        Value::Boolean(b) => MetaValue::Bool(b),
+        Value::Datetime(datetime) => MetaValue::String(datetime.to_string()),
+        Value::Array(arr) => MetaValue::Array(arr.into_iter().map(toml_to_meta).collect()),
+        Value::Table(table) => MetaValue::Table(
+            table
+                .into_iter()
+                .map(|(k, v)| (k, toml_to_meta(v)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_split_frontmatter() {
+    let (meta, rest) = split_frontmatter(
+        "---\ntitle: Hello\ndate: 2024-01-02\ntags: [rust, 笔记]\ncover: a.png\n---\n# Body\n",
+    )
+    .unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Hello"));
+    assert_eq!(meta.date.unwrap().unix_timestamp(), 1704153600);
+    assert_eq!(meta.tags, vec!["rust".to_string(), "笔记".to_string()]);
+    assert_eq!(
+        meta.extra.get("cover"),
+        Some(&MetaValue::String("a.png".to_string()))
+    );
+    assert_eq!(rest, "# Body\n");
+
+    let (meta, rest) = split_frontmatter(
+        "+++\ntitle = \"Hello\"\ndate = 2024-01-02T00:00:00Z\ndraft = true\naliases = [\"old\"]\n+++\nbody",
+    )
+    .unwrap();
+    assert_eq!(meta.title.as_deref(), Some("Hello"));
+    assert_eq!(meta.date.unwrap().unix_timestamp(), 1704153600);
+    assert!(meta.draft);
+    assert_eq!(meta.aliases, vec!["old".to_string()]);
+    assert_eq!(rest, "body");
+
+    let (meta, rest) = split_frontmatter("# No frontmatter\n---\n").unwrap();
+    assert_eq!(meta, FrontMatter::default());
+    assert_eq!(rest, "# No frontmatter\n---\n");
+}
+
+#[cfg(test)]
+#[test]
+fn test_from_attributes_dates() {
+    let date = |value| {
+        FrontMatter::from_attributes([("date", value)])
+            .map(|meta| meta.date.unwrap().unix_timestamp())
+    };
+    assert_eq!(date("<2024-01-02 Tue 10:00>").unwrap(), 1704189600);
+    assert_eq!(date("Tue, 02 Jan 2024 10:00:00 +0000").unwrap(), 1704189600);
+    assert_eq!(date("2024-01-02T10:00:00+08:00").unwrap(), 1704160800);
+    let err = date("January 2, 2024").unwrap_err();
+    assert!(err.to_string().contains("unsupported date format"));
+}
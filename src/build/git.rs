@@ -0,0 +1,202 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use time::UtcDateTime;
+
+/// Separates the commits in the output of `git log`
+const COMMIT_MARKER: char = '\u{1}';
+
+/// Created and updated time of every file in a git repository, collected by a
+/// single `git log` walk
+///
+/// Renames are followed, so a post moved into another folder keeps the created
+/// time of its original path.
+#[derive(Debug, Clone, Default)]
+pub struct GitTimes {
+    root: Option<PathBuf>,
+    /// path relative to the repository root -> (created, updated)
+    times: HashMap<String, (i64, i64)>,
+    /// Paths with uncommitted changes, including untracked ones
+    dirty: HashSet<String>,
+}
+
+impl GitTimes {
+    /// Walk the history of the repository containing `dir`
+    ///
+    /// Returns an empty map if `dir` is not inside a git repository.
+    pub fn collect(dir: impl AsRef<Path>) -> Self {
+        let dir = dir.as_ref();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .current_dir(dir)
+                .args(args)
+                .output()
+                .ok()
+                .filter(|out| out.status.success())
+                .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+        };
+
+        let Some(root) = git(&["rev-parse", "--show-toplevel"]) else {
+            return Self::default();
+        };
+        let log = git(&[
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--name-status",
+            "-M",
+            "--format=%x01%ct",
+        ])
+        .unwrap_or_default();
+        let status =
+            git(&["status", "--porcelain", "-z", "--untracked-files=all"]).unwrap_or_default();
+
+        Self {
+            root: Path::new(root.trim()).canonicalize().ok(),
+            times: Self::parse_log(&log),
+            dirty: Self::parse_status(&status),
+        }
+    }
+
+    /// Parse the output of `git status --porcelain -z`
+    ///
+    /// Each entry is `XY path`, renames and copies are followed by an extra entry of
+    /// the original path.
+    fn parse_status(status: &str) -> HashSet<String> {
+        let mut dirty = HashSet::new();
+        let mut entries = status.split('\0').filter(|s| !s.is_empty());
+        while let Some(entry) = entries.next() {
+            let (Some(xy), Some(path)) = (entry.get(..2), entry.get(3..)) else {
+                continue;
+            };
+            if xy.contains(['R', 'C']) {
+                entries.next();
+            }
+            dirty.insert(path.to_string());
+        }
+        dirty
+    }
+
+    /// Parse the output of `git log --name-status -M --format=%x01%ct`
+    ///
+    /// Commits are listed from the newest to the oldest, so walking them in order the
+    /// first commit touching a file is its updated time, and the last one is its created
+    /// time. `aliases` maps the paths in older commits to the current path of the file,
+    /// `None` means the path belongs to a file that no longer exists.
+    fn parse_log(log: &str) -> HashMap<String, (i64, i64)> {
+        let mut times: HashMap<String, (i64, i64)> = HashMap::new();
+        let mut aliases: HashMap<String, Option<String>> = HashMap::new();
+
+        for commit in log.split(COMMIT_MARKER).filter(|s| !s.trim().is_empty()) {
+            let mut lines = commit.lines();
+            let Some(ts) = lines.next().and_then(|s| s.trim().parse::<i64>().ok()) else {
+                continue;
+            };
+
+            for line in lines.filter(|l| !l.is_empty()) {
+                let mut fields = line.split('\t');
+                let (Some(status), Some(path)) = (fields.next(), fields.next()) else {
+                    continue;
+                };
+                // `R<score>\told\tnew`
+                let (path, renamed_from) = match fields.next() {
+                    Some(new_path) if status.starts_with('R') => (new_path, Some(path)),
+                    _ => (path, None),
+                };
+
+                let current = aliases
+                    .get(path)
+                    .cloned()
+                    .unwrap_or_else(|| Some(path.to_string()));
+                let Some(current) = current else {
+                    continue;
+                };
+
+                match status.chars().next() {
+                    Some('D') => {
+                        // the file at this path before the deletion is another one
+                        aliases.insert(path.to_string(), None);
+                        continue;
+                    }
+                    Some('A') | Some('R') => {
+                        aliases.insert(path.to_string(), None);
+                    }
+                    _ => {}
+                }
+                if let Some(old) = renamed_from {
+                    aliases.insert(old.to_string(), Some(current.clone()));
+                }
+
+                times
+                    .entry(current)
+                    .and_modify(|(created, _)| *created = ts)
+                    .or_insert((ts, ts));
+            }
+        }
+        times
+    }
+
+    /// The path relative to the repository root, with `/` as the separator
+    fn key(&self, path: &Path) -> Option<String> {
+        let root = self.root.as_ref()?;
+        let path = path.canonicalize().ok()?;
+        let path = path.strip_prefix(root).ok()?;
+        Some(
+            path.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+
+    /// `(created, updated)` of the file, which are the times of the commit first
+    /// adding it and the last commit touching it
+    ///
+    /// Returns `None` if the file has never been committed.
+    pub fn times(&self, path: impl AsRef<Path>) -> Option<(UtcDateTime, UtcDateTime)> {
+        let (created, updated) = *self.times.get(&self.key(path.as_ref())?)?;
+        Some((
+            UtcDateTime::from_unix_timestamp(created).ok()?,
+            UtcDateTime::from_unix_timestamp(updated).ok()?,
+        ))
+    }
+
+    /// Whether the file has changes not committed yet, or is not tracked at all
+    pub fn is_uncommitted(&self, path: impl AsRef<Path>) -> bool {
+        self.key(path.as_ref())
+            .is_some_and(|key| self.dirty.contains(&key))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_log() {
+    let log = "\u{1}400\n\nM\tposts/sub/a.md\nM\tposts/b.md\n\
+               \u{1}300\n\nR100\tposts/a.md\tposts/sub/a.md\n\
+               \u{1}200\n\nA\tposts/b.md\nD\tposts/c.md\nM\tposts/a.md\n\
+               \u{1}150\n\nD\tposts/b.md\n\
+               \u{1}100\n\nA\tposts/a.md\nA\tposts/b.md\nA\tposts/c.md\n";
+    let times = GitTimes::parse_log(log);
+
+    // 重命名后仍保留最初的创建时间
+    assert_eq!(times.get("posts/sub/a.md"), Some(&(100, 400)));
+    assert_eq!(times.get("posts/a.md"), None);
+    // 删除后重新添加的文件从重新添加时算起
+    assert_eq!(times.get("posts/b.md"), Some(&(200, 400)));
+    assert_eq!(times.get("posts/c.md"), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_status() {
+    let status = " M posts/a.md\0?? posts/new.md\0R  posts/c.md\0posts/b.md\0";
+    let dirty = GitTimes::parse_status(status);
+
+    assert!(dirty.contains("posts/a.md"));
+    assert!(dirty.contains("posts/new.md"));
+    assert!(dirty.contains("posts/c.md"));
+    assert!(!dirty.contains("posts/b.md"));
+}
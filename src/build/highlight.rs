@@ -0,0 +1,136 @@
+use std::sync::LazyLock;
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag, TagEnd};
+use syntect::{
+    highlighting::{Theme, ThemeSet},
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use crate::build::utils;
+
+/// Classes of the highlighted tokens are prefixed with `hl-`, like `hl-keyword`
+pub const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Highlight `code` into class-based `<span>`s, returns `None` if `lang` is unknown
+pub fn highlight_code(code: &str, lang: &str) -> Option<String> {
+    let syntax = SYNTAX_SET
+        .find_syntax_by_token(lang)
+        .or_else(|| SYNTAX_SET.find_syntax_by_name(lang))?;
+    let mut generator =
+        ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAX_SET, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .ok()?;
+    }
+    Some(generator.finalize())
+}
+
+/// Replace the fenced code blocks with a language in the markdown events by highlighted
+/// html, the code of an unknown language is kept as is
+pub fn highlight_code_blocks<'a>(
+    events: impl Iterator<Item = Event<'a>>,
+) -> impl Iterator<Item = Event<'a>> {
+    let mut block: Option<(CowStr<'a>, String)> = None;
+    events.flat_map(move |event| match (&mut block, event) {
+        (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) if !info.is_empty() => {
+            block = Some((info, String::new()));
+            vec![]
+        }
+        (Some((_, code)), Event::Text(text)) => {
+            code.push_str(&text);
+            vec![]
+        }
+        (Some(_), Event::End(TagEnd::CodeBlock)) => {
+            let (info, code) = block.take().unwrap();
+            // info string 可能带有额外的属性，比如 `rust,ignore` 或 `rust {.class}`
+            let lang = info
+                .split([' ', ',', '{'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let html = match highlight_code(&code, &lang) {
+                Some(html) => html,
+                None => utils::escape_xml(&code),
+            };
+            vec![Event::Html(
+                format!(
+                    r#"<pre><code class="language-{}">{html}</code></pre>"#,
+                    utils::escape_xml(&lang)
+                )
+                .into(),
+            )]
+        }
+        (_, event) => vec![event],
+    })
+}
+
+/// Names of the bundled themes, like `InspiredGitHub` and `base16-ocean.dark`
+pub fn theme_names() -> impl Iterator<Item = &'static str> {
+    THEME_SET.themes.keys().map(|name| name.as_str())
+}
+
+/// The stylesheet of a bundled theme for the classes emitted by [`highlight_code`]
+///
+/// Besides the token colors, it sets `--pre-bg` and `--pre-code-text` to the background
+/// and foreground of the theme.
+pub fn theme_css(name: &str) -> Result<String, anyhow::Error> {
+    let theme = THEME_SET
+        .themes
+        .get(name)
+        .ok_or_else(|| anyhow::anyhow!("unknown highlight theme: {name}"))?;
+    theme_to_css(theme)
+}
+
+fn theme_to_css(theme: &Theme) -> Result<String, anyhow::Error> {
+    let mut css = String::from(":root {\n");
+    for (var, color) in [
+        ("--pre-bg", theme.settings.background),
+        ("--pre-code-text", theme.settings.foreground),
+    ] {
+        if let Some(c) = color {
+            css.push_str(&format!("    {var}: #{:02x}{:02x}{:02x};\n", c.r, c.g, c.b));
+        }
+    }
+    css.push_str("}\n");
+    css.push_str(&css_for_theme_with_class_style(theme, CLASS_STYLE)?);
+    Ok(css)
+}
+
+/// `light` by default, and `dark` when the system prefers a dark color scheme
+pub fn auto_theme_css(light: &str, dark: &str) -> Result<String, anyhow::Error> {
+    let dark = theme_css(dark)?
+        .lines()
+        .map(|line| format!("  {line}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Ok(format!(
+        "{}\n@media (prefers-color-scheme: dark) {{\n{dark}\n}}\n",
+        theme_css(light)?
+    ))
+}
+
+#[cfg(test)]
+#[test]
+fn test_highlight_code_blocks() {
+    let markdown = "```rust,ignore\nfn main() {}\n```\n\n```unknown-lang\na < b\n```\n";
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(
+        &mut html,
+        highlight_code_blocks(pulldown_cmark::Parser::new(markdown)),
+    );
+
+    assert!(html.contains(r#"<pre><code class="language-rust"><span class="hl-source hl-rust">"#));
+    assert!(html.contains(r#"<span class="hl-storage hl-type hl-function hl-rust">fn</span>"#));
+    assert!(html.contains(r#"<pre><code class="language-unknown-lang">a &lt; b"#));
+
+    let css = theme_css("InspiredGitHub").unwrap();
+    assert!(css.starts_with(":root {\n    --pre-bg: #ffffff;"));
+    assert!(css.contains(".hl-keyword"));
+    assert!(theme_css("no-such-theme").is_err());
+}
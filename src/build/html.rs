@@ -0,0 +1,160 @@
+use anyhow::Context;
+use scraper::{Html, Node, Selector};
+
+use crate::build::{
+    frontmatter::FrontMatter,
+    post::Post,
+    utils::{escape_xml, local_ref_path},
+    Entity, Parser,
+};
+
+/// Parses hand-written or exported html pages
+///
+/// The `<body>` is the content of the post, and the `<head>` provides the metadata:
+/// `<title>` is the title, and the `<meta name="..." content="...">` tags go through
+/// [`FrontMatter::from_attributes`], so `description` is the summary, `date` the date
+/// and `keywords` the tags.
+///
+/// Relative urls are resolved against `<base href>` if there is one, the local files
+/// they refer to end up in [`Post::ref_paths`] like the ones of the markdown posts.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlPostParser;
+
+impl Parser for HtmlPostParser {
+    type Output = Post;
+    fn try_parse(&self, entity: Entity) -> Result<Self::Output, anyhow::Error> {
+        let source = std::str::from_utf8(&entity.content)?;
+        let mut document = Html::parse_document(source);
+
+        let select = |selector: &str| Selector::parse(selector).unwrap();
+        let title = document
+            .select(&select("head title"))
+            .next()
+            .map(|title| title.text().collect::<String>().trim().to_string())
+            .filter(|title| !title.is_empty());
+        let metas = document
+            .select(&select("head meta[name][content]"))
+            .filter_map(|meta| {
+                let meta = meta.value();
+                Some((
+                    meta.attr("name")?.to_string(),
+                    meta.attr("content")?.to_string(),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let meta = FrontMatter::from_attributes(
+            title
+                .as_deref()
+                .map(|title| ("title", title))
+                .into_iter()
+                .chain(
+                    metas
+                        .iter()
+                        .map(|(name, content)| (name.as_str(), content.as_str())),
+                ),
+        )?;
+
+        let base = document
+            .select(&select("head base[href]"))
+            .next()
+            .and_then(|base| base.value().attr("href"))
+            .map(|href| href[..href.rfind('/').map_or(0, |i| i + 1)].to_string())
+            .filter(|base| !base.is_empty());
+        if let Some(base) = base {
+            resolve_relative_urls(&mut document, &base);
+        }
+
+        let body = document
+            .select(&select("body"))
+            .next()
+            .context("no body in html")?;
+        let mut content_html = body.inner_html().trim().to_string();
+        // 和 markdown 一样，正文以 `<h1>` 标题开头
+        if let Some(title) = &meta.title
+            && body.select(&select("h1")).next().is_none()
+        {
+            content_html = format!("<h1>{}</h1>\n{content_html}", escape_xml(title));
+        }
+
+        Ok(Post::from_html_entity_with_meta(content_html, entity, meta))
+    }
+}
+
+/// Prefix the relative urls in `src`, `href`, `poster` and `srcset` with `base`
+fn resolve_relative_urls(document: &mut Html, base: &str) {
+    let resolve = |url: &str| match local_ref_path(url) {
+        Some(_) => format!("{base}{}", url.trim().trim_start_matches("./")),
+        None => url.to_string(),
+    };
+    let ids = document
+        .root_element()
+        .descendent_elements()
+        .filter(|element| element.value().name() != "base")
+        .map(|element| element.id())
+        .collect::<Vec<_>>();
+    for id in ids {
+        let Some(mut node) = document.tree.get_mut(id) else {
+            continue;
+        };
+        let Node::Element(element) = node.value() else {
+            continue;
+        };
+        for (name, value) in element.attrs.iter_mut() {
+            let resolved = match &*name.local {
+                "src" | "href" | "poster" => resolve(value),
+                // `a.png 1x, b.png 2x`
+                "srcset" => value
+                    .split(',')
+                    .map(|candidate| {
+                        let candidate = candidate.trim();
+                        let (url, descriptor) =
+                            candidate.split_once(' ').unwrap_or((candidate, ""));
+                        format!("{} {descriptor}", resolve(url)).trim().to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                _ => continue,
+            };
+            *value = resolved.into();
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_html_parser() {
+    let dir = crate::build::utils::temp_dir_with(&[(
+        "page.html",
+        r##"<!DOCTYPE html>
+<html>
+<head>
+  <title>Exported Page</title>
+  <meta name="description" content="A page exported to html">
+  <meta name="date" content="2024-01-02">
+  <base href="page_files/">
+</head>
+<body>
+  <p>Hello <img src="./avatar.jpg" srcset="avatar.jpg 1x, avatar@2x.jpg 2x"></p>
+  <p><a href="https://example.com">remote</a> <a href="#top">anchor</a></p>
+</body>
+</html>
+"##,
+    )]);
+    let path = dir.path().join("page.html");
+
+    let post = HtmlPostParser
+        .try_parse(Entity::new(&path).unwrap())
+        .unwrap();
+    assert_eq!(post.title, "Exported Page");
+    assert_eq!(
+        post.meta.summary.as_deref(),
+        Some("A page exported to html")
+    );
+    assert_eq!(post.meta.date.unwrap().unix_timestamp(), 1704153600);
+    assert!(post.content_html.starts_with("<h1>Exported Page</h1>"));
+    assert!(post.content_html.contains(r##"<a href="#top">anchor</a>"##));
+    assert_eq!(
+        post.ref_paths,
+        vec!["page_files/avatar.jpg", "page_files/avatar@2x.jpg"]
+    );
+}
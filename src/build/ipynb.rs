@@ -0,0 +1,325 @@
+use std::path::Path;
+
+use anyhow::Context;
+use base64::Engine;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::build::{
+    frontmatter::{split_frontmatter, FrontMatter},
+    markdown::{self, MarkdownOptions},
+    obsidian::Vault,
+    post::Post,
+    utils, Entity, Parser,
+};
+
+/// Parses Jupyter notebooks (nbformat 4)
+///
+/// Markdown cells go through the markdown pipeline, code cells become fenced code
+/// blocks in the notebook language, and the stored outputs are rendered after them.
+/// Images in the outputs are extracted next to the notebook into `<slug>_files/`, so
+/// they are picked up by [`get_assets_trunk_data`](crate::build::get_assets_trunk_data).
+///
+/// The first markdown cell may start with a frontmatter.
+#[derive(Debug, Clone, Default)]
+pub struct IpynbPostParser {
+    markdown: MarkdownOptions,
+    vault: Option<Vault>,
+}
+
+impl IpynbPostParser {
+    pub fn new(markdown: MarkdownOptions) -> Self {
+        Self {
+            markdown,
+            vault: None,
+        }
+    }
+    /// Resolve the attachments of the markdown cells by their names in the Obsidian
+    /// vault at `root`, like `MarkdownPostParser::with_vault`
+    pub fn with_vault(mut self, root: impl AsRef<Path>) -> Self {
+        self.vault = Some(Vault::new(root));
+        self
+    }
+}
+
+impl Parser for IpynbPostParser {
+    type Output = Post;
+    fn try_parse(&self, entity: Entity) -> Result<Self::Output, anyhow::Error> {
+        let notebook: Value =
+            serde_json::from_slice(&entity.content).context("invalid notebook json")?;
+        let lang = notebook
+            .pointer("/metadata/language_info/name")
+            .or_else(|| notebook.pointer("/metadata/kernelspec/language"))
+            .and_then(Value::as_str)
+            .unwrap_or("python");
+        let cells = notebook
+            .get("cells")
+            .and_then(Value::as_array)
+            .context("no cells in notebook, only nbformat 4 is supported")?;
+
+        let assets = Assets {
+            dir: entity.path.parent().unwrap_or(Path::new(".")),
+            name: format!("{}_files", slug::slugify(entity.base_name())),
+        };
+        let mut meta = FrontMatter::default();
+        let mut html = Vec::new();
+        for (i, cell) in cells.iter().enumerate() {
+            let source = multiline(cell.get("source"));
+            match cell.get("cell_type").and_then(Value::as_str) {
+                Some("markdown") => {
+                    let source = if html.is_empty() {
+                        let (m, source) = split_frontmatter(&source)?;
+                        meta = m;
+                        source.to_string()
+                    } else {
+                        source
+                    };
+                    let mut cell_html = markdown::to_html(&source, &self.markdown);
+                    if let Some(vault) = &self.vault {
+                        cell_html = vault.resolve_attachments(&cell_html, &entity.path);
+                    }
+                    html.push(cell_html);
+                }
+                Some("code") => {
+                    if !source.trim().is_empty() {
+                        html.push(markdown::to_html(
+                            &fenced_code(&source, lang),
+                            &self.markdown,
+                        ));
+                    }
+                    let outputs = cell.get("outputs").and_then(Value::as_array);
+                    for (j, output) in outputs.into_iter().flatten().enumerate() {
+                        let output = self
+                            .render_output(output, &assets, &format!("output_{i}_{j}"))
+                            .with_context(|| format!("failed to render output {j} of cell {i}"))?;
+                        html.extend(output);
+                    }
+                }
+                // raw cells are not rendered
+                _ => {}
+            }
+        }
+
+        Ok(Post::from_html_entity_with_meta(
+            html.join("\n"),
+            entity,
+            meta,
+        ))
+    }
+}
+
+/// Where the images in the outputs are extracted to
+struct Assets<'a> {
+    /// The directory of the notebook
+    dir: &'a Path,
+    /// The name of the assets directory, relative to `dir`
+    name: String,
+}
+
+impl Assets<'_> {
+    /// Write `data` into the assets directory, returns the path relative to the notebook
+    fn write(&self, file_name: &str, data: &[u8]) -> Result<String, anyhow::Error> {
+        let dir = self.dir.join(&self.name);
+        let path = dir.join(file_name);
+        // 内容不变时不重写，避免触发 `rerun-if-changed`
+        if std::fs::read(&path).ok().as_deref() != Some(data) {
+            std::fs::create_dir_all(&dir)?;
+            std::fs::write(&path, data)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        Ok(format!("{}/{file_name}", self.name))
+    }
+}
+
+impl IpynbPostParser {
+    fn render_output(
+        &self,
+        output: &Value,
+        assets: &Assets,
+        name: &str,
+    ) -> Result<Option<String>, anyhow::Error> {
+        let html = match output.get("output_type").and_then(Value::as_str) {
+            Some("stream") => {
+                let stream = output
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("stdout");
+                let text = multiline(output.get("text"));
+                format!(
+                    r#"<pre class="output output-{stream}">{}</pre>"#,
+                    utils::escape_xml(&text)
+                )
+            }
+            Some("error") => {
+                let traceback = output
+                    .get("traceback")
+                    .and_then(Value::as_array)
+                    .map(|lines| {
+                        lines
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default();
+                format!(
+                    r#"<pre class="output output-error">{}</pre>"#,
+                    utils::escape_xml(&strip_ansi(&traceback))
+                )
+            }
+            Some("execute_result" | "display_data") => {
+                let Some(data) = output.get("data") else {
+                    return Ok(None);
+                };
+                let Some(html) = self.render_data(data, assets, name)? else {
+                    return Ok(None);
+                };
+                format!(r#"<div class="output">{html}</div>"#)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(html))
+    }
+
+    /// Render the richest representation of a mime bundle, in the order of nbconvert
+    fn render_data(
+        &self,
+        data: &Value,
+        assets: &Assets,
+        name: &str,
+    ) -> Result<Option<String>, anyhow::Error> {
+        let get = |mime: &str| data.get(mime).map(|v| multiline(Some(v)));
+
+        if let Some(html) = get("text/html") {
+            return Ok(Some(html));
+        }
+        if let Some(svg) = get("image/svg+xml") {
+            let src = assets.write(&format!("{name}.svg"), svg.as_bytes())?;
+            return Ok(Some(format!(r#"<img src="{src}" alt="{name}">"#)));
+        }
+        for (mime, ext) in [
+            ("image/png", "png"),
+            ("image/jpeg", "jpg"),
+            ("image/gif", "gif"),
+        ] {
+            if let Some(encoded) = get(mime) {
+                // base64 数据可能被按行切开
+                let encoded = encoded.split_whitespace().collect::<String>();
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .with_context(|| format!("invalid base64 {mime}"))?;
+                let src = assets.write(&format!("{name}.{ext}"), &bytes)?;
+                return Ok(Some(format!(r#"<img src="{src}" alt="{name}">"#)));
+            }
+        }
+        if let Some(markdown) = get("text/markdown") {
+            return Ok(Some(markdown::to_html(&markdown, &self.markdown)));
+        }
+        if let Some(text) = get("text/plain") {
+            return Ok(Some(format!("<pre>{}</pre>", utils::escape_xml(&text))));
+        }
+        Ok(None)
+    }
+}
+
+/// The notebook stores multiline strings either as a string or as a list of lines
+fn multiline(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// A fence longer than any backtick run in `code`
+fn fenced_code(code: &str, lang: &str) -> String {
+    let longest = Regex::new("`+")
+        .unwrap()
+        .find_iter(code)
+        .map(|m| m.len())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}\n", code.trim_end_matches('\n'))
+}
+
+/// Remove the terminal colors in the tracebacks
+fn strip_ansi(text: &str) -> String {
+    Regex::new(r"\x1b\[[0-9;]*[A-Za-z]")
+        .unwrap()
+        .replace_all(text, "")
+        .into_owned()
+}
+
+#[cfg(test)]
+#[test]
+fn test_ipynb_parser() {
+    let notebook = serde_json::json!({
+        "nbformat": 4,
+        "metadata": { "language_info": { "name": "python" } },
+        "cells": [
+            { "cell_type": "markdown", "source": ["---\n", "tags: [jupyter]\n", "---\n", "# Title\n"] },
+            {
+                "cell_type": "code",
+                "source": "print(1 < 2)",
+                "outputs": [
+                    { "output_type": "stream", "name": "stdout", "text": ["True\n"] },
+                    { "output_type": "display_data", "data": { "image/png": "iVBORw0K\nGgo=", "text/plain": "<Figure>" } },
+                    { "output_type": "error", "traceback": ["\u{1b}[0;31mZeroDivisionError\u{1b}[0m"] }
+                ]
+            }
+        ]
+    });
+    let dir = crate::build::utils::temp_dir_with(&[("My Notebook.ipynb", &notebook.to_string())]);
+    let path = dir.path().join("My Notebook.ipynb");
+
+    let post = IpynbPostParser::default()
+        .try_parse(Entity::new(&path).unwrap())
+        .unwrap();
+    assert_eq!(post.title, "Title");
+    assert_eq!(post.meta.tags, vec!["jupyter"]);
+    assert!(post
+        .content_html
+        .contains(r#"<pre><code class="language-python">"#));
+    assert!(post
+        .content_html
+        .contains(r#"<pre class="output output-stdout">True"#));
+    assert!(post
+        .content_html
+        .contains(r#"<pre class="output output-error">ZeroDivisionError</pre>"#));
+    assert!(post
+        .content_html
+        .contains(r#"<img src="my-notebook_files/output_1_1.png""#));
+    assert_eq!(post.ref_paths, vec!["my-notebook_files/output_1_1.png"]);
+    assert_eq!(
+        std::fs::read(dir.path().join("my-notebook_files/output_1_1.png")).unwrap(),
+        b"\x89PNG\r\n\x1a\n"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_ipynb_obsidian_vault() {
+    let notebook = serde_json::json!({
+        "nbformat": 4,
+        "metadata": {},
+        "cells": [
+            { "cell_type": "markdown", "source": "# Plot %%draft%%\n\n![[chart.png]] [[Other Note]]\n" }
+        ]
+    });
+    let dir = crate::build::utils::temp_dir_with(&[
+        ("posts/notes/plot.ipynb", &notebook.to_string()),
+        ("posts/attachments/chart.png", ""),
+    ]);
+
+    let options = crate::build::ParseOptions::default().with_obsidian_vault(dir.path());
+    let html =
+        &crate::build::parse_posts_report(dir.path().join("posts"), &options).posts[0].content_html;
+    assert!(!html.contains("draft"));
+    assert!(html.contains(r#"<img src="/posts/attachments/chart.png""#));
+    // 默认的解析器只处理普通的 markdown
+    let plain = IpynbPostParser::default()
+        .try_parse(Entity::new(dir.path().join("posts/notes/plot.ipynb")).unwrap())
+        .unwrap();
+    assert!(plain.content_html.contains("%%draft%%"));
+}
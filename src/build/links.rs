@@ -0,0 +1,766 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use scraper::{Html, Selector};
+
+use crate::build::{
+    post::Post,
+    report::BuildReport,
+    section::{self, Section},
+    toc,
+    utils::{escape_xml, local_ref_path, normalize_path, percent_decode},
+    ParseOptions,
+};
+
+/// The urls of the wiki links are prefixed with it by the markdown parser, like
+/// `wiki:Note Title#Heading`
+pub const WIKI_LINK_SCHEME: &str = "wiki:";
+
+/// The names of the attributes holding urls, `data-src` and the like are not included
+const URL_ATTRIBUTES: [&str; 4] = ["href", "src", "poster", "srcset"];
+
+/// A url attribute in the tags of an html
+pub(crate) struct UrlAttribute<'a> {
+    /// One of [`URL_ATTRIBUTES`]
+    pub name: &'static str,
+    /// The value without the quotes, still escaped
+    pub value: &'a str,
+    /// The range of the value in the html, with the quotes
+    range: Range<usize>,
+}
+
+/// The url attributes in the tags of `html`, whose values may be quoted with `"`, `'`
+/// or not at all, like in the raw html of the markdown
+///
+/// The tags in the comments and in `<script>`/`<style>` are skipped.
+pub(crate) fn url_attributes(html: &str) -> Vec<UrlAttribute<'_>> {
+    let bytes = html.as_bytes();
+    let skip = |mut i: usize, f: fn(&u8) -> bool| {
+        while bytes.get(i).is_some_and(f) {
+            i += 1;
+        }
+        i
+    };
+    let mut attributes = Vec::new();
+    let mut i = 0;
+    while let Some(offset) = html[i..].find('<') {
+        i += offset + 1;
+        if html[i..].starts_with("!--") {
+            i = html[i..].find("-->").map_or(html.len(), |end| i + end + 3);
+            continue;
+        }
+        // `</p>`、`<!DOCTYPE html>` 或者文本中的 `<`
+        if !bytes.get(i).is_some_and(u8::is_ascii_alphabetic) {
+            continue;
+        }
+        let tag_start = i;
+        i = skip(i, |b| !b.is_ascii_whitespace() && !matches!(b, b'/' | b'>'));
+        let tag = html[tag_start..i].to_ascii_lowercase();
+
+        loop {
+            i = skip(i, |b| b.is_ascii_whitespace() || *b == b'/');
+            if i >= bytes.len() || bytes[i] == b'>' {
+                break;
+            }
+            let name_start = i;
+            i = skip(i, |b| {
+                !b.is_ascii_whitespace() && !matches!(b, b'/' | b'>' | b'=')
+            });
+            if i == name_start {
+                // 多余的 `=`
+                i += 1;
+                continue;
+            }
+            let name = &html[name_start..i];
+            let equal = skip(i, u8::is_ascii_whitespace);
+            if bytes.get(equal) != Some(&b'=') {
+                continue;
+            }
+            let start = skip(equal + 1, u8::is_ascii_whitespace);
+            let (value, range) = match bytes.get(start) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    let end = html[start + 1..]
+                        .find(quote as char)
+                        .map_or(html.len(), |end| start + 1 + end);
+                    (&html[start + 1..end], start..html.len().min(end + 1))
+                }
+                _ => {
+                    let end = skip(start, |b| !b.is_ascii_whitespace() && *b != b'>');
+                    (&html[start..end], start..end)
+                }
+            };
+            i = range.end;
+            if let Some(name) = URL_ATTRIBUTES
+                .into_iter()
+                .find(|attribute| attribute.eq_ignore_ascii_case(name))
+            {
+                attributes.push(UrlAttribute { name, value, range });
+            }
+        }
+
+        if tag == "script" || tag == "style" {
+            i = html[i..]
+                .to_ascii_lowercase()
+                .find(&format!("</{tag}"))
+                .map_or(html.len(), |end| i + end);
+        }
+    }
+    attributes
+}
+
+/// Replace the values of the url attributes in `html` with the escaped ones returned by
+/// `replace`, which are written double-quoted
+pub(crate) fn replace_url_attributes(
+    html: &str,
+    mut replace: impl FnMut(&UrlAttribute) -> Option<String>,
+) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut last = 0;
+    for attribute in url_attributes(html) {
+        if let Some(value) = replace(&attribute) {
+            output.push_str(&html[last..attribute.range.start]);
+            // 原来没有引号或者是单引号时，值中可能有 `"`
+            output.push('"');
+            output.push_str(&value.replace('"', "&quot;"));
+            output.push('"');
+            last = attribute.range.end;
+        }
+    }
+    output.push_str(&html[last..]);
+    output
+}
+
+/// Rewrite the relative links between the posts to their routes, and report the broken
+/// links as errors
+///
+/// `dir` is the posts directory. A link to the source file of a post, like
+/// `../rust/async.md#usage`, becomes `/posts/rust/async-md#usage`, and a link to a
+/// directory or an `_index` page becomes the route of the section.
+///
+/// A wiki link `[[Note Title#Heading]]` refers to the post by its file name, title, or
+/// slug (case-insensitive, in this order of precedence), and to the heading by its text.
+/// The posts linking to a post end up in its [`Post::backlinks`].
+///
+/// Links to a post or a file that does not exist, `/posts/...` routes that match no
+/// post, and anchors matching no `id` in the target post are reported with the line of
+/// the link in the source file. The posts are still built with the broken links.
+pub fn resolve_links(dir: &Path, options: &ParseOptions, report: &mut BuildReport) {
+    let targets = LinkTargets::new(normalize_path(dir), &report.posts);
+
+    let mut errors = BuildReport::default();
+    for post in &mut report.posts {
+        targets.resolve_page(post, options, &mut errors);
+    }
+    report.diagnostics.extend(errors.diagnostics);
+
+    let mut backlinks: HashMap<String, Vec<String>> = HashMap::new();
+    for post in &report.posts {
+        let slug = post.slug();
+        for target in targets.linked_posts(&post.content_html) {
+            if target == slug {
+                continue;
+            }
+            let sources = backlinks.entry(target).or_default();
+            if !sources.contains(&slug) {
+                sources.push(slug.clone());
+            }
+        }
+    }
+    for post in &mut report.posts {
+        post.backlinks = backlinks.remove(&post.slug()).unwrap_or_default();
+    }
+}
+
+/// Rewrite the links in the pages that are not posts, the `index` page of the site and
+/// the `_index` pages of the `sections`, the same way as [`resolve_links`], and make the
+/// urls of their assets root-absolute like [`absolute_asset_urls`]
+///
+/// `posts` are the ones parsed from `dir`. Returns the broken links.
+pub fn resolve_page_links(
+    dir: &Path,
+    options: &ParseOptions,
+    posts: &[Post],
+    index: &mut Post,
+    sections: &mut [Section],
+) -> BuildReport {
+    let targets = LinkTargets::new(normalize_path(dir), posts);
+
+    let mut report = BuildReport::default();
+    let pages = std::iter::once(index).chain(sections.iter_mut().filter_map(|s| s.index.as_mut()));
+    for page in pages {
+        targets.resolve_page(page, options, &mut report);
+        absolute_asset_urls(dir, std::slice::from_mut(page));
+    }
+    report
+}
+
+/// Make the relative urls left in the posts root-absolute, like `img.png` in
+/// `posts/rust/async.md` becoming `/posts/rust/img.png`
+///
+/// The pages are pre-rendered to `posts/<slug>/index.html`, where a relative url would
+/// resolve against the wrong directory. `dir` is the posts directory, assumed to be at
+/// the root of the assets copied by [`crate::build::get_assets_trunk_data`].
+pub fn absolute_asset_urls(dir: &Path, posts: &mut [Post]) {
+    let dir = normalize_path(dir);
+    let base = dir
+        .file_name()
+        .map(|name| format!("/{}", name.to_string_lossy()))
+        .unwrap_or_default();
+    for post in posts {
+        let Ok(relative) = normalize_path(&post.path)
+            .strip_prefix(&dir)
+            .map(Path::to_path_buf)
+        else {
+            continue;
+        };
+        let page = relative.components().fold(base.clone(), |page, c| {
+            format!("{page}/{}", c.as_os_str().to_string_lossy())
+        });
+        // 页内锚点保持不变
+        let map = |url: &str| match url.starts_with(['#', '?']) {
+            true => None,
+            false => resolve_url(url, &page),
+        };
+        post.content_html = map_urls(&post.content_html, map);
+        post.summary_html = map_urls(&post.summary_html, map);
+    }
+}
+
+/// The posts and sections the links can point to
+struct LinkTargets {
+    /// The posts directory
+    dir: PathBuf,
+    /// Normalized path of the source file -> slug
+    files: HashMap<PathBuf, String>,
+    /// Slug or alias -> ids in the content of the post
+    posts: HashMap<String, HashSet<String>>,
+    /// Slug or alias -> slug
+    slugs: HashMap<String, String>,
+    /// Lowercased file name, title, or slug -> slug, for the wiki links
+    notes: HashMap<String, String>,
+    sections: HashSet<String>,
+}
+
+impl LinkTargets {
+    fn new(dir: PathBuf, posts: &[Post]) -> Self {
+        let mut targets = Self {
+            dir,
+            files: HashMap::new(),
+            posts: HashMap::new(),
+            slugs: HashMap::new(),
+            notes: HashMap::new(),
+            sections: HashSet::new(),
+        };
+        for post in posts {
+            let slug = post.slug();
+            let ids = collect_ids(&post.content_html);
+            for alias in post.aliases() {
+                targets.posts.insert(alias.clone(), ids.clone());
+                targets.slugs.insert(alias, slug.clone());
+            }
+            targets.posts.insert(slug.clone(), ids);
+            targets.slugs.insert(slug.clone(), slug.clone());
+            targets.files.insert(normalize_path(&post.path), slug);
+            for i in 1..=post.entity.section.len() {
+                targets
+                    .sections
+                    .insert(section::section_slug(&post.entity.section[..i]));
+            }
+        }
+
+        let names: [fn(&Post) -> Vec<String>; 3] = [
+            |post| {
+                let mut path = post.entity.section.clone();
+                path.push(post.base_name());
+                vec![post.base_name(), path.join("/")]
+            },
+            |post| vec![post.title.clone()],
+            |post| vec![post.slug()],
+        ];
+        for name in names {
+            for post in posts {
+                for name in name(post) {
+                    targets
+                        .notes
+                        .entry(name.to_lowercase())
+                        .or_insert_with(|| post.slug());
+                }
+            }
+        }
+        targets
+    }
+
+    /// Rewrite the links of `post`, reporting the broken ones with their lines
+    fn resolve_page(&self, post: &mut Post, options: &ParseOptions, report: &mut BuildReport) {
+        let path = normalize_path(&post.path);
+        let ids = collect_ids(&post.content_html);
+        let resolve =
+            |attribute: &str, url: &str| self.resolve(&path, &ids, attribute, url, options);
+
+        let mut broken = Vec::new();
+        post.content_html = rewrite_urls(&post.content_html, resolve, &mut broken);
+        post.summary_html = rewrite_urls(&post.summary_html, resolve, &mut Vec::new());
+
+        let source = String::from_utf8_lossy(&post.content);
+        let mut reported = HashSet::new();
+        for (url, message) in broken {
+            if reported.insert(url.clone()) {
+                report.error_at(&post.path, find_line(&source, &url), message);
+            }
+        }
+    }
+
+    /// The slug of the post `[[note]]` refers to
+    fn find_note(&self, note: &str) -> Option<&String> {
+        let note = note.trim();
+        let note = note.strip_suffix(".md").unwrap_or(note);
+        self.notes.get(&note.to_lowercase())
+    }
+
+    /// Slugs of the posts linked by `/posts/...` in `html`
+    fn linked_posts(&self, html: &str) -> Vec<String> {
+        url_attributes(html)
+            .into_iter()
+            .filter(|attribute| attribute.name == "href")
+            .filter_map(|attribute| {
+                let route = attribute.value.strip_prefix("/posts/")?;
+                let slug = route.split(['#', '?']).next().unwrap_or_default();
+                self.slugs.get(slug.trim_end_matches('/')).cloned()
+            })
+            .collect()
+    }
+
+    /// The url to replace `url` in `attribute` of the post at `path` with, if it is a
+    /// link to another post, or why it is broken
+    fn resolve(
+        &self,
+        path: &Path,
+        ids: &HashSet<String>,
+        attribute: &str,
+        url: &str,
+        options: &ParseOptions,
+    ) -> Result<Option<String>, String> {
+        let url = url.trim().replace("&amp;", "&");
+        if let Some(wiki) = url.strip_prefix(WIKI_LINK_SCHEME) {
+            return self.resolve_wiki(&percent_decode(wiki), ids).map(Some);
+        }
+        let (target, fragment) = match url.split_once('#') {
+            Some((target, fragment)) => (target, Some(fragment)),
+            None => (url.as_str(), None),
+        };
+
+        if let Some(slug) = target.strip_prefix("/posts/") {
+            if attribute != "href" {
+                return Ok(None);
+            }
+            let slug = slug
+                .split('?')
+                .next()
+                .unwrap_or_default()
+                .trim_end_matches('/');
+            return match self.posts.get(slug) {
+                Some(ids) => check_fragment(fragment, ids).map(|_| None),
+                None if self.sections.contains(slug) => Ok(None),
+                None => Err(format!("link to a post that does not exist: {url}")),
+            };
+        }
+
+        let Some(target) = local_ref_path(target) else {
+            if attribute == "href" && target.is_empty() {
+                check_fragment(fragment, ids)?;
+            }
+            return Ok(None);
+        };
+        let file = normalize_path(
+            &path
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(percent_decode(target)),
+        );
+
+        if attribute == "href" {
+            if let Some(slug) = self.files.get(&file) {
+                check_fragment(fragment, &self.posts[slug])?;
+                return Ok(Some(with_fragment(format!("/posts/{slug}"), fragment)));
+            }
+            if let Some(route) = self.section_route(&file) {
+                return Ok(Some(with_fragment(route, fragment)));
+            }
+        }
+        if !file.exists() {
+            let extension = file.extension().and_then(|s| s.to_str()).unwrap_or("");
+            let kind = if attribute == "href" && options.parsers.supports(extension) {
+                "post"
+            } else {
+                "file"
+            };
+            return Err(format!("link to a {kind} that does not exist: {target}"));
+        }
+        Ok(None)
+    }
+
+    /// The url of `[[note#heading]]`, `[[#heading]]` refers to the post itself
+    fn resolve_wiki(&self, wiki: &str, ids: &HashSet<String>) -> Result<String, String> {
+        let (note, heading) = match wiki.split_once('#') {
+            Some((note, heading)) => (note, Some(heading)),
+            None => (wiki, None),
+        };
+        // `#^block` 引用不支持，只链接到文章
+        let fragment = heading
+            .filter(|heading| !heading.starts_with('^'))
+            .map(toc::slugify);
+        let fragment = fragment.as_deref();
+        if note.trim().is_empty() {
+            check_fragment(fragment, ids)?;
+            return Ok(with_fragment(String::new(), fragment));
+        }
+        let slug = self
+            .find_note(note)
+            .ok_or_else(|| format!("wiki link to a note that does not exist: [[{wiki}]]"))?;
+        check_fragment(fragment, &self.posts[slug])?;
+        Ok(with_fragment(format!("/posts/{slug}"), fragment))
+    }
+
+    /// The route of the section of a directory or an `_index` page under the posts directory
+    fn section_route(&self, file: &Path) -> Option<String> {
+        let dir = if section::is_section_index(file) {
+            file.parent()?
+        } else if file.is_dir() {
+            file
+        } else {
+            return None;
+        };
+        let path = dir
+            .strip_prefix(&self.dir)
+            .ok()?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        if path.is_empty() {
+            return Some("/posts".to_string());
+        }
+        let slug = section::section_slug(&path);
+        self.sections
+            .contains(&slug)
+            .then(|| format!("/posts/{slug}"))
+    }
+}
+
+/// Replace the urls in `html` resolved by `resolve`, collecting the broken ones
+fn rewrite_urls(
+    html: &str,
+    resolve: impl Fn(&str, &str) -> Result<Option<String>, String>,
+    broken: &mut Vec<(String, String)>,
+) -> String {
+    replace_url_attributes(html, |attribute| {
+        let url = attribute.value;
+        // `a.png 1x, b.png 2x`
+        let urls = match attribute.name {
+            "srcset" => url
+                .split(',')
+                .filter_map(|candidate| candidate.split_whitespace().next())
+                .collect(),
+            _ => vec![url],
+        };
+        let mut resolved = None;
+        for url in urls {
+            match resolve(attribute.name, url) {
+                Ok(url) => resolved = resolved.or(url),
+                Err(message) => broken.push((source_text(url), message)),
+            }
+        }
+        resolved
+            .filter(|_| attribute.name != "srcset")
+            .map(|url| escape_xml(&url))
+    })
+}
+
+/// How `url` is written in the markdown source, to find the line of it
+fn source_text(url: &str) -> String {
+    let url = url.replace("&amp;", "&");
+    match url.strip_prefix(WIKI_LINK_SCHEME) {
+        Some(wiki) => format!("[[{}", percent_decode(wiki)),
+        None => url,
+    }
+}
+
+/// Replace the urls in the url attributes of `html` with the ones returned by `map`
+///
+/// `map` gets the unescaped urls, and each url of a `srcset` separately.
+pub(crate) fn map_urls(html: &str, map: impl Fn(&str) -> Option<String>) -> String {
+    replace_url_attributes(html, |attribute| {
+        let mut mapped = false;
+        let mut map = |url: &str| match map(&url.replace("&amp;", "&")) {
+            Some(url) => {
+                mapped = true;
+                escape_xml(&url)
+            }
+            None => url.to_string(),
+        };
+        let value = match attribute.name {
+            // `a.png 1x, b.png 2x`
+            "srcset" => attribute
+                .value
+                .split(',')
+                .map(|candidate| match candidate.trim().split_once(' ') {
+                    Some((url, descriptor)) => format!("{} {}", map(url), descriptor.trim()),
+                    None => map(candidate.trim()),
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => map(attribute.value),
+        };
+        mapped.then_some(value)
+    })
+}
+
+/// `url` as a path from the root of the site, resolved against the page at `page`, like
+/// `/posts/rust/async-md`, or `None` if it is remote or already such a path
+pub(crate) fn resolve_url(url: &str, page: &str) -> Option<String> {
+    let url = url.trim();
+    let joined = match url.chars().next()? {
+        '#' | '?' => format!("{page}{url}"),
+        _ => {
+            local_ref_path(url)?;
+            format!("{}{url}", &page[..=page.rfind('/')?])
+        }
+    };
+    // 去掉路径中的 `.` 和 `..`，保留 `?` 和 `#` 之后的部分
+    let (path, suffix) = joined.split_at(joined.find(['?', '#']).unwrap_or(joined.len()));
+    let mut segments = Vec::new();
+    for segment in path.split('/').skip(1) {
+        match segment {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    if path.ends_with("/.") || path.ends_with("/..") {
+        segments.push("");
+    }
+    Some(format!("/{}{suffix}", segments.join("/")))
+}
+
+fn check_fragment(fragment: Option<&str>, ids: &HashSet<String>) -> Result<(), String> {
+    match fragment.map(percent_decode) {
+        Some(id) if !id.is_empty() && !ids.contains(&id) => {
+            Err(format!("link to a heading that does not exist: #{id}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn with_fragment(route: String, fragment: Option<&str>) -> String {
+    match fragment {
+        Some(fragment) => format!("{route}#{fragment}"),
+        None => route,
+    }
+}
+
+/// The `id`s (and the `name`s of `<a>`) in the html, the targets of the anchors
+fn collect_ids(html: &str) -> HashSet<String> {
+    let html = Html::parse_fragment(html);
+    let selector = Selector::parse("[id], a[name]").unwrap();
+    html.select(&selector)
+        .filter_map(|element| {
+            let element = element.value();
+            element.id().or_else(|| element.attr("name"))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// 1-based line of the first occurrence of `url` in the source
+fn find_line(source: &str, url: &str) -> Option<usize> {
+    let offset = source.find(url)?;
+    Some(source[..offset].matches('\n').count() + 1)
+}
+
+#[cfg(test)]
+#[test]
+fn test_resolve_links() {
+    let dir = crate::build::utils::temp_dir_with(&[
+        (
+            "a.md",
+            "# A
+
+## Usage
+
+[b](sub/b.md#intro) [sub](sub/) [self](#usage) [abs](/posts/sub/b-md)
+
+[missing](missing.md)
+
+![image](missing.png)
+
+[anchor](sub/b.md#nope)
+
+<a href='sub/c.md'>c</a> <a href=d.md>d</a>
+
+<a href='gone.md'>gone</a> <img src=gone.png>
+",
+        ),
+        ("sub/b.md", "# B\n\n## Intro\n\n[back](../a.md)\n"),
+        ("sub/c.md", "# C\n"),
+        ("d.md", "# D\n"),
+    ]);
+
+    let options = ParseOptions::default();
+    let report = crate::build::parse_posts_report(dir.path(), &options);
+    let a = report.posts.iter().find(|p| p.slug() == "a-md").unwrap();
+    let b = report
+        .posts
+        .iter()
+        .find(|p| p.slug() == "sub/b-md")
+        .unwrap();
+    assert!(a
+        .content_html
+        .contains(r#"<a href="/posts/sub/b-md#intro">b</a>"#));
+    assert!(a.content_html.contains(r#"<a href="/posts/sub">sub</a>"#));
+    assert!(a.content_html.contains(r##"<a href="#usage">self</a>"##));
+    assert!(b.content_html.contains(r#"<a href="/posts/a-md">back</a>"#));
+    assert!(a
+        .content_html
+        .contains(r#"<a href="/posts/sub/c-md">c</a>"#));
+    assert!(a.content_html.contains(r#"<a href="/posts/d-md">d</a>"#));
+    let post = |slug: &str| report.posts.iter().find(|p| p.slug() == slug).unwrap();
+    assert_eq!(post("sub/c-md").backlinks, vec!["a-md"]);
+    assert_eq!(post("d-md").backlinks, vec!["a-md"]);
+
+    let errors = report
+        .errors()
+        .map(|e| (e.line, e.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        vec![
+            (Some(7), "link to a post that does not exist: missing.md"),
+            (Some(9), "link to a file that does not exist: missing.png"),
+            (Some(11), "link to a heading that does not exist: #nope"),
+            (Some(15), "link to a post that does not exist: gone.md"),
+            (Some(15), "link to a file that does not exist: gone.png"),
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_wiki_links() {
+    let dir = crate::build::utils::temp_dir_with(&[
+        (
+            "index.md",
+            "# Top\n\n## Usage\n\n[[Note Title#Some Heading|see]] [[other title]] [[#Usage]]\n\n[[Missing]]\n",
+        ),
+        ("Note Title.md", "# Note\n\n## Some Heading\n"),
+        ("other.md", "# Other Title\n\n[[index]]\n"),
+    ]);
+
+    let markdown = crate::build::markdown::MarkdownOptions::default().with_wikilinks(true);
+    let options = ParseOptions::default().with_markdown(markdown);
+    let report = crate::build::parse_posts_report(dir.path(), &options);
+    let post = |slug: &str| report.posts.iter().find(|p| p.slug() == slug).unwrap();
+    let html = &post("index-md").content_html;
+    assert!(html.contains(r#"<a href="/posts/note-title-md#some-heading">see</a>"#));
+    assert!(html.contains(r#"<a href="/posts/other-md">other title</a>"#));
+    assert!(html.contains(r##"<a href="#usage">#Usage</a>"##));
+
+    assert_eq!(post("note-title-md").backlinks, vec!["index-md"]);
+    assert_eq!(post("other-md").backlinks, vec!["index-md"]);
+    assert_eq!(post("index-md").backlinks, vec!["other-md"]);
+
+    let errors = report
+        .errors()
+        .map(|e| (e.line, e.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        vec![(
+            Some(7),
+            "wiki link to a note that does not exist: [[Missing]]"
+        )]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_absolute_asset_urls() {
+    let dir = crate::build::utils::temp_dir_with(&[
+        (
+            "posts/rust/async.md",
+            "# Async\n\n![diagram](img/a.png) [pdf](../files/b.pdf?v=1) [top](#async) [remote](https://example.com/c.png)\n\n<img src='img/a.png' alt='raw'>\n",
+        ),
+        ("posts/rust/img/a.png", ""),
+        ("posts/files/b.pdf", ""),
+    ]);
+
+    let report =
+        crate::build::parse_posts_report(dir.path().join("posts"), &ParseOptions::default());
+    let html = &report.posts[0].content_html;
+    assert!(html.contains(r#"src="/posts/rust/img/a.png""#));
+    assert!(html.contains(r#"href="/posts/files/b.pdf?v=1""#));
+    assert!(html.contains(r##"href="#async""##));
+    assert!(html.contains(r#"href="https://example.com/c.png""#));
+    assert!(html.contains(r#"<img src="/posts/rust/img/a.png" alt='raw'>"#));
+}
+
+#[cfg(test)]
+#[test]
+fn test_map_urls() {
+    let html = r#"<img data-src="a.png" src="a.png" srcset="a.png 1x, b.png 2x">"#;
+    assert_eq!(
+        map_urls(html, |url| Some(format!("/x/{url}"))),
+        r#"<img data-src="a.png" src="/x/a.png" srcset="/x/a.png 1x, /x/b.png 2x">"#
+    );
+
+    let html = r#"<a title='x' href='a.md'>a</a><img src=b.png alt=b><video poster = 'c.png'>"#;
+    assert_eq!(
+        map_urls(html, |url| Some(format!("/x/{url}"))),
+        r#"<a title='x' href="/x/a.md">a</a><img src="/x/b.png" alt=b><video poster = "/x/c.png">"#
+    );
+    // 注释和脚本中的不是属性
+    let html = r#"<!-- <img src='a.png'> --><script>let s = "<img src='a.png'>";</script>"#;
+    assert_eq!(map_urls(html, |url| Some(format!("/x/{url}"))), html);
+}
+
+#[cfg(test)]
+#[test]
+fn test_resolve_page_links() {
+    let dir = crate::build::utils::temp_dir_with(&[
+        (
+            "index.md",
+            "# Home\n\n[a](posts/a.md) [missing](posts/missing.md)\n",
+        ),
+        ("posts/a.md", "# A\n"),
+        (
+            "posts/rust/_index.md",
+            "# Rust\n\n[a](../a.md) ![icon](img.png)\n",
+        ),
+        ("posts/rust/img.png", ""),
+    ]);
+    let posts_dir = dir.path().join("posts");
+
+    let options = ParseOptions::default();
+    let posts = crate::build::parse_posts_report(&posts_dir, &options).posts;
+    let mut index =
+        Post::try_from(crate::build::Entity::new(dir.path().join("index.md")).unwrap()).unwrap();
+    let mut sections = section::parse_sections(&posts_dir);
+    let report = resolve_page_links(&posts_dir, &options, &posts, &mut index, &mut sections);
+
+    assert!(index
+        .content_html
+        .contains(r#"<a href="/posts/a-md">a</a>"#));
+    let rust = &sections[0].index.as_ref().unwrap().content_html;
+    assert!(rust.contains(r#"<a href="/posts/a-md">a</a>"#));
+    assert!(rust.contains(r#"src="/posts/rust/img.png""#));
+
+    let errors = report.errors().collect::<Vec<_>>();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "link to a post that does not exist: posts/missing.md"
+    );
+}
@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+
+use pulldown_cmark::{Event, LinkType, Options, Tag};
+
+use crate::build::{highlight, links, math, obsidian};
+
+/// Markdown extensions enabled for the posts
+///
+/// By default GFM tables, footnotes, task lists, strikethrough, heading attributes and
+/// math are enabled, along with code highlighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    tables: bool,
+    footnotes: bool,
+    task_lists: bool,
+    strikethrough: bool,
+    heading_attributes: bool,
+    math: bool,
+    wikilinks: bool,
+    smart_punctuation: bool,
+    highlight: bool,
+    obsidian: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            footnotes: true,
+            task_lists: true,
+            strikethrough: true,
+            heading_attributes: true,
+            math: true,
+            wikilinks: false,
+            smart_punctuation: false,
+            highlight: true,
+            obsidian: false,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    pub fn with_tables(mut self, tables: bool) -> Self {
+        self.tables = tables;
+        self
+    }
+    /// `[^1]` references and `[^1]: ...` definitions
+    pub fn with_footnotes(mut self, footnotes: bool) -> Self {
+        self.footnotes = footnotes;
+        self
+    }
+    /// `- [x] done` list items
+    pub fn with_task_lists(mut self, task_lists: bool) -> Self {
+        self.task_lists = task_lists;
+        self
+    }
+    /// `~~deleted~~`
+    pub fn with_strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = strikethrough;
+        self
+    }
+    /// `# Heading {#id .class}`
+    pub fn with_heading_attributes(mut self, heading_attributes: bool) -> Self {
+        self.heading_attributes = heading_attributes;
+        self
+    }
+    /// `$inline$` and `$$display$$` math, rendered to MathML
+    pub fn with_math(mut self, math: bool) -> Self {
+        self.math = math;
+        self
+    }
+    /// `[[Note Title]]` and `[[Note Title#Heading|text]]` links to other posts, resolved
+    /// by [`links::resolve_links`], default is `false`, always on in the Obsidian mode
+    pub fn with_wikilinks(mut self, wikilinks: bool) -> Self {
+        self.wikilinks = wikilinks;
+        self
+    }
+    /// Curly quotes, en/em dashes and ellipses, default is `false`
+    pub fn with_smart_punctuation(mut self, smart_punctuation: bool) -> Self {
+        self.smart_punctuation = smart_punctuation;
+        self
+    }
+    /// Highlight the fenced code blocks, see [`highlight`]
+    pub fn with_highlight(mut self, highlight: bool) -> Self {
+        self.highlight = highlight;
+        self
+    }
+    /// Obsidian callouts, `![[...]]` embeds and `%%comments%%`, see [`obsidian`], default
+    /// is `false`, wiki links are always enabled with it
+    pub fn with_obsidian(mut self, obsidian: bool) -> Self {
+        self.obsidian = obsidian;
+        self
+    }
+
+    fn pulldown_cmark_options(&self) -> Options {
+        let mut options = Options::empty();
+        for (enabled, option) in [
+            (self.tables, Options::ENABLE_TABLES),
+            (self.footnotes, Options::ENABLE_FOOTNOTES),
+            (self.task_lists, Options::ENABLE_TASKLISTS),
+            (self.strikethrough, Options::ENABLE_STRIKETHROUGH),
+            (self.heading_attributes, Options::ENABLE_HEADING_ATTRIBUTES),
+            (self.math, Options::ENABLE_MATH),
+            (self.wikilinks || self.obsidian, Options::ENABLE_WIKILINKS),
+            (self.smart_punctuation, Options::ENABLE_SMART_PUNCTUATION),
+        ] {
+            options.set(option, enabled);
+        }
+        options
+    }
+}
+
+/// Convert markdown to html with `options`
+pub fn to_html(markdown: &str, options: &MarkdownOptions) -> String {
+    let markdown = match options.obsidian {
+        true => Cow::Owned(obsidian::strip_comments(markdown)),
+        false => Cow::Borrowed(markdown),
+    };
+    let parser = pulldown_cmark::Parser::new_ext(&markdown, options.pulldown_cmark_options());
+    let parser: Box<dyn Iterator<Item = Event<'_>>> = match options.obsidian {
+        true => Box::new(obsidian::render_obsidian(parser).into_iter()),
+        false => Box::new(parser),
+    };
+    let parser = math::render_math(parser).map(mark_wikilink);
+    let mut html = String::new();
+    if options.highlight {
+        pulldown_cmark::html::push_html(&mut html, highlight::highlight_code_blocks(parser));
+    } else {
+        pulldown_cmark::html::push_html(&mut html, parser);
+    }
+    html
+}
+
+/// Prefix the url of a wiki link with [`links::WIKI_LINK_SCHEME`] to resolve it later
+fn mark_wikilink(event: Event<'_>) -> Event<'_> {
+    match event {
+        Event::Start(Tag::Link {
+            link_type: link_type @ LinkType::WikiLink { .. },
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: format!("{}{dest_url}", links::WIKI_LINK_SCHEME).into(),
+            title,
+            id,
+        }),
+        event => event,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_to_html() {
+    let markdown = "\
+~~strike~~
+
+| a | b |
+|---|---|
+| 1 | 2 |
+
+- [x] done
+- [ ] todo
+
+note[^1]
+
+[^1]: the footnote
+
+## Heading {#custom-id}
+";
+    let html = to_html(markdown, &MarkdownOptions::default());
+    assert!(html.contains("<del>strike</del>"));
+    assert!(html.contains("<table>"));
+    assert!(html.contains(r#"<input disabled="" type="checkbox" checked=""/>"#));
+    assert!(html.contains(r#"<sup class="footnote-reference">"#));
+    assert!(html.contains(r#"<div class="footnote-definition" id="1">"#));
+    assert!(html.contains(r#"<h2 id="custom-id">Heading</h2>"#));
+
+    let html = to_html("$x$\n\n$$\\frac{1}{2}$$", &MarkdownOptions::default());
+    assert!(html.contains("<p><math><mi>x</mi></math></p>"));
+    assert!(html.contains(r#"<math display="block"><mfrac>"#));
+
+    let html = to_html("[[Note#Usage|see]]", &MarkdownOptions::default());
+    assert_eq!(html, "<p>[[Note#Usage|see]]</p>\n");
+    let html = to_html(
+        "[[Note#Usage|see]]",
+        &MarkdownOptions::default().with_wikilinks(true),
+    );
+    assert_eq!(html, "<p><a href=\"wiki:Note#Usage\">see</a></p>\n");
+
+    let html = to_html(
+        markdown,
+        &MarkdownOptions::default().with_strikethrough(false),
+    );
+    assert!(html.contains("~~strike~~"));
+}
@@ -0,0 +1,938 @@
+//! A LaTeX to MathML converter for the common subset of math notes
+//!
+//! Supports fractions, roots, scripts, greek letters and symbols, functions, fonts,
+//! accents, `\left`/`\right` and the matrix-like environments. Unknown commands and the
+//! commands missing their arguments are rendered as `<merror>`, so that they are visible
+//! instead of silently dropped or malformed.
+
+use pulldown_cmark::Event;
+
+use crate::build::utils;
+
+/// Replace the math events of pulldown-cmark with MathML
+pub fn render_math<'a>(events: impl Iterator<Item = Event<'a>>) -> impl Iterator<Item = Event<'a>> {
+    events.map(|event| match event {
+        Event::InlineMath(tex) => Event::InlineHtml(latex_to_mathml(&tex, false).into()),
+        Event::DisplayMath(tex) => Event::Html(latex_to_mathml(&tex, true).into()),
+        event => event,
+    })
+}
+
+/// Convert `tex` to a `<math>` element, `display` for `$$...$$`
+pub fn latex_to_mathml(tex: &str, display: bool) -> String {
+    let mut parser = TexParser {
+        chars: tex.chars().collect(),
+        pos: 0,
+        display,
+    };
+    let mut atoms = Vec::new();
+    loop {
+        let (rows, end) = parser.parse_rows();
+        atoms.extend(rows_to_atoms(rows));
+        // 多余的 `}`、`\right` 和 `\end` 不会结束整个公式
+        let stray = match end {
+            End::Eof => break,
+            End::Brace => "}",
+            End::Right => "\\right",
+            _ => "\\end",
+        };
+        atoms.push(Atom::new(format!(
+            "<merror>{}</merror>",
+            elem("mtext", stray)
+        )));
+    }
+    let display = if display { r#" display="block""# } else { "" };
+    format!("<math{display}>{}</math>", mrow(atoms))
+}
+
+/// A rendered element, `limits` means the scripts of it go above and below in display mode
+struct Atom {
+    mathml: String,
+    limits: bool,
+}
+
+impl Atom {
+    fn new(mathml: String) -> Self {
+        Self {
+            mathml,
+            limits: false,
+        }
+    }
+}
+
+/// What stopped [`TexParser::parse_seq`]
+#[derive(Debug, PartialEq)]
+enum End {
+    Eof,
+    Brace,
+    Right,
+    Amp,
+    NewRow,
+    Env,
+}
+
+fn mrow(atoms: Vec<Atom>) -> String {
+    match atoms.len() {
+        1 => atoms.into_iter().next().unwrap().mathml,
+        _ => format!(
+            "<mrow>{}</mrow>",
+            atoms.into_iter().map(|a| a.mathml).collect::<String>()
+        ),
+    }
+}
+
+/// The cells of the rows as a `<mtable>`
+fn mtable(rows: Vec<Vec<Vec<Atom>>>, columnalign: &str) -> String {
+    format!(
+        "<mtable{columnalign}>{}</mtable>",
+        rows.into_iter()
+            .map(|row| format!(
+                "<mtr>{}</mtr>",
+                row.into_iter()
+                    .map(|cell| format!("<mtd>{}</mtd>", mrow(cell)))
+                    .collect::<String>()
+            ))
+            .collect::<String>()
+    )
+}
+
+/// The atoms of the only cell, or a `<mtable>` of the cells split by `&` and `\\`
+fn rows_to_atoms(mut rows: Vec<Vec<Vec<Atom>>>) -> Vec<Atom> {
+    if rows.len() == 1 && rows[0].len() == 1 {
+        return rows.pop().unwrap().pop().unwrap();
+    }
+    vec![Atom::new(mtable(rows, ""))]
+}
+
+fn elem(tag: &str, content: &str) -> String {
+    format!("<{tag}>{}</{tag}>", utils::escape_xml(content))
+}
+
+fn mo(op: &str) -> String {
+    elem("mo", op)
+}
+
+struct TexParser {
+    chars: Vec<char>,
+    pos: usize,
+    display: bool,
+}
+
+impl TexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// The name after a `\`, either a run of letters or a single other character
+    fn command_name(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        if self.pos == start && self.peek().is_some() {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// The raw text inside `{...}`, used by `\text` and `\begin`
+    fn raw_group(&mut self) -> String {
+        self.skip_whitespace();
+        if self.peek() != Some('{') {
+            return String::new();
+        }
+        self.pos += 1;
+        let mut depth = 0;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        text
+    }
+
+    /// The raw text of a `{...}` group or a single character
+    fn raw_arg(&mut self) -> String {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.raw_group(),
+            Some(c) => {
+                self.pos += 1;
+                c.to_string()
+            }
+            None => String::new(),
+        }
+    }
+
+    /// The argument of a font command, letters and digits are mapped to the styled
+    /// unicode characters since MathML Core only supports `mathvariant="normal"`
+    fn styled_arg(&mut self, variant: &str) -> String {
+        let raw = self.raw_arg();
+        if !raw
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c.is_whitespace())
+        {
+            // 含有其他命令时退化为普通的公式
+            let mut parser = TexParser {
+                chars: raw.chars().collect(),
+                pos: 0,
+                display: self.display,
+            };
+            return mrow(parser.parse_seq().0);
+        }
+        let atoms = raw
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match (c.is_ascii_digit(), variant) {
+                (true, _) => elem("mn", &styled_char(c, variant).to_string()),
+                (false, "normal") => format!(r#"<mi mathvariant="normal">{c}</mi>"#),
+                (false, _) => elem("mi", &styled_char(c, variant).to_string()),
+            })
+            .map(Atom::new)
+            .collect();
+        mrow(atoms)
+    }
+
+    /// A `{...}` group or a single token, `None` if it is missing
+    fn parse_arg(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => {
+                self.pos += 1;
+                let (rows, _) = self.parse_rows();
+                Some(mrow(rows_to_atoms(rows)))
+            }
+            // 结束当前分组或者单元格，不属于这个参数
+            '}' | '&' => None,
+            c if c.is_ascii_digit() => {
+                self.pos += 1;
+                Some(elem("mn", &c.to_string()))
+            }
+            _ => self.parse_atom().map(|a| a.mathml),
+        }
+    }
+
+    /// The source from `start` as an error, like `\frac{a}` missing an argument
+    fn error_from(&self, start: usize) -> String {
+        let source: String = self.chars[start..self.pos].iter().collect();
+        format!("<merror>{}</merror>", elem("mtext", source.trim()))
+    }
+
+    /// An optional `[...]` argument
+    fn parse_optional_arg(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        if self.peek() != Some('[') {
+            return None;
+        }
+        self.pos += 1;
+        let mut atoms = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == ']' {
+                self.pos += 1;
+                break;
+            }
+            match self.parse_atom() {
+                Some(atom) => atoms.push(atom),
+                None => break,
+            }
+        }
+        Some(mrow(atoms))
+    }
+
+    /// Parse until the end of the input, a `}`, `\right`, `&`, `\\` or `\end`
+    fn parse_seq(&mut self) -> (Vec<Atom>, End) {
+        let mut atoms: Vec<Atom> = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let Some(c) = self.peek() else {
+                return (atoms, End::Eof);
+            };
+            match c {
+                '}' => {
+                    self.pos += 1;
+                    return (atoms, End::Brace);
+                }
+                '&' => {
+                    self.pos += 1;
+                    return (atoms, End::Amp);
+                }
+                '^' | '_' => {
+                    let base = atoms
+                        .pop()
+                        .unwrap_or(Atom::new("<mrow></mrow>".to_string()));
+                    atoms.push(self.parse_scripts(base));
+                }
+                '\'' => {
+                    let mut primes = String::new();
+                    while self.peek() == Some('\'') {
+                        self.pos += 1;
+                        primes.push('′');
+                    }
+                    let base = atoms
+                        .pop()
+                        .map(|a| a.mathml)
+                        .unwrap_or("<mrow></mrow>".to_string());
+                    atoms.push(Atom::new(format!("<msup>{base}{}</msup>", mo(&primes))));
+                }
+                '\\' => {
+                    let start = self.pos;
+                    self.pos += 1;
+                    match self.command_name().as_str() {
+                        "\\" => return (atoms, End::NewRow),
+                        "right" => return (atoms, End::Right),
+                        "end" => {
+                            self.raw_group();
+                            return (atoms, End::Env);
+                        }
+                        _ => {
+                            self.pos = start;
+                            atoms.extend(self.parse_atom());
+                        }
+                    }
+                }
+                _ => atoms.extend(self.parse_atom()),
+            }
+        }
+    }
+
+    /// [`Self::parse_seq`] continued over `&` and `\\`, the cells of each row
+    fn parse_rows(&mut self) -> (Vec<Vec<Vec<Atom>>>, End) {
+        let mut rows: Vec<Vec<Vec<Atom>>> = vec![vec![]];
+        let end = loop {
+            let (atoms, end) = self.parse_seq();
+            rows.last_mut().unwrap().push(atoms);
+            match end {
+                End::Amp => {}
+                End::NewRow => rows.push(vec![]),
+                end => break end,
+            }
+        };
+        // `\\` 结尾时会多出一个空行
+        if rows.len() > 1
+            && rows
+                .last()
+                .is_some_and(|row| row.len() == 1 && row[0].is_empty())
+        {
+            rows.pop();
+        }
+        (rows, end)
+    }
+
+    /// `^` and `_` after `base`, in either order
+    fn parse_scripts(&mut self, base: Atom) -> Atom {
+        let (mut sub, mut sup) = (None, None);
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.pos += 1;
+                    sub = Some(
+                        self.parse_arg()
+                            .unwrap_or_else(|| self.error_from(self.pos - 1)),
+                    );
+                }
+                Some('^') if sup.is_none() => {
+                    self.pos += 1;
+                    sup = Some(
+                        self.parse_arg()
+                            .unwrap_or_else(|| self.error_from(self.pos - 1)),
+                    );
+                }
+                _ => break,
+            }
+        }
+        let under_over = base.limits && self.display;
+        let base = base.mathml;
+        let mathml = match (sub, sup, under_over) {
+            (Some(sub), Some(sup), true) => format!("<munderover>{base}{sub}{sup}</munderover>"),
+            (Some(sub), None, true) => format!("<munder>{base}{sub}</munder>"),
+            (None, Some(sup), true) => format!("<mover>{base}{sup}</mover>"),
+            (Some(sub), Some(sup), false) => format!("<msubsup>{base}{sub}{sup}</msubsup>"),
+            (Some(sub), None, false) => format!("<msub>{base}{sub}</msub>"),
+            (None, Some(sup), false) => format!("<msup>{base}{sup}</msup>"),
+            (None, None, _) => base,
+        };
+        Atom::new(mathml)
+    }
+
+    /// A single token, group or command
+    fn parse_atom(&mut self) -> Option<Atom> {
+        self.skip_whitespace();
+        let c = self.peek()?;
+        self.pos += 1;
+        let atom = match c {
+            '{' => {
+                let (rows, _) = self.parse_rows();
+                Atom::new(mrow(rows_to_atoms(rows)))
+            }
+            '\\' => {
+                let name = self.command_name();
+                return Some(self.parse_command(&name));
+            }
+            c if c.is_ascii_digit() => {
+                let start = self.pos - 1;
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.pos += 1;
+                }
+                let number: String = self.chars[start..self.pos].iter().collect();
+                // 末尾的 `.` 是句号而不是小数点
+                match number.strip_suffix('.') {
+                    Some(number) => {
+                        self.pos -= 1;
+                        Atom::new(elem("mn", number))
+                    }
+                    None => Atom::new(elem("mn", &number)),
+                }
+            }
+            c if c.is_alphabetic() => Atom::new(elem("mi", &c.to_string())),
+            '~' => Atom::new(r#"<mspace width="0.25em"></mspace>"#.to_string()),
+            c => Atom::new(mo(&c.to_string())),
+        };
+        Some(atom)
+    }
+
+    fn parse_command(&mut self, name: &str) -> Atom {
+        let start = self.pos - name.chars().count() - 1;
+        if let Some(symbol) = greek(name) {
+            let mathml = if symbol.chars().next().is_some_and(char::is_uppercase) {
+                format!(r#"<mi mathvariant="normal">{symbol}</mi>"#)
+            } else {
+                elem("mi", symbol)
+            };
+            return Atom::new(mathml);
+        }
+        if let Some(symbol) = identifier(name) {
+            return Atom::new(elem("mi", symbol));
+        }
+        if let Some(symbol) = operator(name) {
+            return Atom::new(mo(symbol));
+        }
+        if let Some((symbol, limits)) = big_operator(name) {
+            return Atom {
+                mathml: format!(r#"<mo largeop="true">{symbol}</mo>"#),
+                limits,
+            };
+        }
+        if let Some((function, limits)) = function(name) {
+            return Atom {
+                mathml: elem("mi", function),
+                limits,
+            };
+        }
+        if let Some(width) = space(name) {
+            return Atom::new(format!(r#"<mspace width="{width}"></mspace>"#));
+        }
+        if let Some((accent, stretchy, over)) = accent(name) {
+            let Some(arg) = self.parse_arg() else {
+                return Atom::new(self.error_from(start));
+            };
+            let stretchy = if stretchy { "true" } else { "false" };
+            let (tag, attr) = if over {
+                ("mover", "accent")
+            } else {
+                ("munder", "accentunder")
+            };
+            return Atom::new(format!(
+                r#"<{tag} {attr}="true">{arg}<mo stretchy="{stretchy}">{accent}</mo></{tag}>"#
+            ));
+        }
+        if let Some(variant) = font(name) {
+            let mathml = match name {
+                "text" | "textrm" | "mbox" => elem("mtext", &self.raw_arg()),
+                "operatorname" => elem("mi", &self.raw_arg()),
+                _ => self.styled_arg(variant),
+            };
+            return Atom::new(mathml);
+        }
+
+        let mathml = match name {
+            "frac" | "dfrac" | "tfrac" => match (self.parse_arg(), self.parse_arg()) {
+                (Some(num), Some(den)) => format!("<mfrac>{num}{den}</mfrac>"),
+                _ => self.error_from(start),
+            },
+            "binom" => match (self.parse_arg(), self.parse_arg()) {
+                (Some(n), Some(k)) => format!(
+                    r#"<mrow><mo>(</mo><mfrac linethickness="0">{n}{k}</mfrac><mo>)</mo></mrow>"#
+                ),
+                _ => self.error_from(start),
+            },
+            "sqrt" => match (self.parse_optional_arg(), self.parse_arg()) {
+                (Some(index), Some(arg)) => format!("<mroot>{arg}{index}</mroot>"),
+                (None, Some(arg)) => format!("<msqrt>{arg}</msqrt>"),
+                _ => self.error_from(start),
+            },
+            "left" => {
+                let open = self.delimiter();
+                let (rows, _) = self.parse_rows();
+                let close = self.delimiter();
+                let inner = rows_to_atoms(rows)
+                    .into_iter()
+                    .map(|a| a.mathml)
+                    .collect::<String>();
+                format!("<mrow>{open}{inner}{close}</mrow>")
+            }
+            "middle" => self.delimiter(),
+            "big" | "Big" | "bigg" | "Bigg" | "bigl" | "Bigl" | "biggl" | "Biggl" | "bigr"
+            | "Bigr" | "biggr" | "Biggr" | "bigm" | "Bigm" => self.delimiter(),
+            "begin" => self.parse_env(),
+            "limits" | "nolimits" | "displaystyle" | "textstyle" | "scriptstyle" => String::new(),
+            _ => format!("<merror>{}</merror>", elem("mtext", &format!("\\{name}"))),
+        };
+        Atom::new(mathml)
+    }
+
+    /// The delimiter after `\left`, `\right` and the like, `.` means none
+    fn delimiter(&mut self) -> String {
+        self.skip_whitespace();
+        let Some(c) = self.peek() else {
+            return String::new();
+        };
+        self.pos += 1;
+        match c {
+            '.' => String::new(),
+            '\\' => {
+                let name = self.command_name();
+                let symbol = match name.as_str() {
+                    "{" | "lbrace" => "{",
+                    "}" | "rbrace" => "}",
+                    "|" | "Vert" => "‖",
+                    "vert" => "|",
+                    _ => operator(&name).unwrap_or_default(),
+                };
+                mo(symbol)
+            }
+            c => mo(&c.to_string()),
+        }
+    }
+
+    /// `\begin{name} ... \end{name}` rendered as a `<mtable>`
+    fn parse_env(&mut self) -> String {
+        let name = self.raw_group();
+        let name = name.trim_end_matches('*');
+        if name == "array" {
+            // 列格式说明，比如 `{cc|c}`
+            self.raw_group();
+        }
+
+        let (rows, _) = self.parse_rows();
+
+        let columnalign = match name {
+            "cases" => r#" columnalign="left left""#,
+            "aligned" | "align" | "split" => r#" columnalign="right left""#,
+            _ => "",
+        };
+        let table = mtable(rows, columnalign);
+        let (open, close) = match name {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" => ("{", "}"),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            "cases" => ("{", ""),
+            _ => return table,
+        };
+        let close = if close.is_empty() {
+            String::new()
+        } else {
+            mo(close)
+        };
+        format!("<mrow>{}{table}{close}</mrow>", mo(open))
+    }
+}
+
+fn greek(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        _ => return None,
+    })
+}
+
+/// Ordinary symbols
+fn identifier(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "emptyset" | "varnothing" => "∅",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "angle" => "∠",
+        "triangle" => "△",
+        "top" => "⊤",
+        "bot" => "⊥",
+        _ => return None,
+    })
+}
+
+/// Binary operators, relations, arrows, delimiters and punctuation
+fn operator(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "pm" => "±",
+        "mp" => "∓",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "⋅",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "le" | "leq" => "≤",
+        "ge" | "geq" => "≥",
+        "ne" | "neq" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "ll" => "≪",
+        "gg" => "≫",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "wedge" | "land" => "∧",
+        "vee" | "lor" => "∨",
+        "neg" | "lnot" => "¬",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "to" | "rightarrow" => "→",
+        "gets" | "leftarrow" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" => "⇔",
+        "implies" => "⟹",
+        "iff" => "⟺",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "prime" => "′",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "{" | "lbrace" => "{",
+        "}" | "rbrace" => "}",
+        "|" => "‖",
+        "vert" => "|",
+        "#" => "#",
+        "%" => "%",
+        "&" => "&",
+        "$" => "$",
+        "_" => "_",
+        _ => return None,
+    })
+}
+
+/// `(symbol, limits)`
+fn big_operator(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "sum" => ("∑", true),
+        "prod" => ("∏", true),
+        "coprod" => ("∐", true),
+        "bigcup" => ("⋃", true),
+        "bigcap" => ("⋂", true),
+        "bigoplus" => ("⨁", true),
+        "bigotimes" => ("⨂", true),
+        "int" => ("∫", false),
+        "iint" => ("∬", false),
+        "iiint" => ("∭", false),
+        "oint" => ("∮", false),
+        _ => return None,
+    })
+}
+
+/// `(name, limits)`
+fn function(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "sin" => ("sin", false),
+        "cos" => ("cos", false),
+        "tan" => ("tan", false),
+        "cot" => ("cot", false),
+        "sec" => ("sec", false),
+        "csc" => ("csc", false),
+        "arcsin" => ("arcsin", false),
+        "arccos" => ("arccos", false),
+        "arctan" => ("arctan", false),
+        "sinh" => ("sinh", false),
+        "cosh" => ("cosh", false),
+        "tanh" => ("tanh", false),
+        "log" => ("log", false),
+        "ln" => ("ln", false),
+        "lg" => ("lg", false),
+        "exp" => ("exp", false),
+        "det" => ("det", true),
+        "dim" => ("dim", false),
+        "ker" => ("ker", false),
+        "deg" => ("deg", false),
+        "arg" => ("arg", false),
+        "hom" => ("hom", false),
+        "gcd" => ("gcd", true),
+        "Pr" => ("Pr", true),
+        "lim" => ("lim", true),
+        "liminf" => ("lim inf", true),
+        "limsup" => ("lim sup", true),
+        "max" => ("max", true),
+        "min" => ("min", true),
+        "sup" => ("sup", true),
+        "inf" => ("inf", true),
+        "argmax" => ("arg max", true),
+        "argmin" => ("arg min", true),
+        _ => return None,
+    })
+}
+
+fn space(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "," => "0.1667em",
+        ":" | ">" => "0.2222em",
+        ";" => "0.2778em",
+        " " => "0.25em",
+        "!" => "-0.1667em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    })
+}
+
+/// `(symbol, stretchy, over)`
+fn accent(name: &str) -> Option<(&'static str, bool, bool)> {
+    Some(match name {
+        "hat" => ("^", false, true),
+        "widehat" => ("^", true, true),
+        "bar" => ("¯", false, true),
+        "overline" => ("‾", true, true),
+        "vec" => ("→", false, true),
+        "overrightarrow" => ("→", true, true),
+        "tilde" => ("~", false, true),
+        "widetilde" => ("~", true, true),
+        "dot" => ("˙", false, true),
+        "ddot" => ("¨", false, true),
+        "overbrace" => ("⏞", true, true),
+        "underline" => ("_", true, false),
+        "underbrace" => ("⏟", true, false),
+        _ => return None,
+    })
+}
+
+/// The `mathvariant` of a font command
+fn font(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "text" | "textrm" | "mbox" | "mathrm" | "operatorname" => "normal",
+        "mathbf" | "textbf" | "boldsymbol" | "bm" => "bold",
+        "mathit" | "textit" => "italic",
+        "mathbb" => "double-struck",
+        "mathcal" => "script",
+        "mathfrak" => "fraktur",
+        "mathsf" | "textsf" => "sans-serif",
+        "mathtt" | "texttt" => "monospace",
+        _ => return None,
+    })
+}
+
+/// `c` in the Mathematical Alphanumeric Symbols block
+fn styled_char(c: char, variant: &str) -> char {
+    // 这些字符在 Letterlike Symbols 中已经存在，所以在该区块中被保留了
+    let letterlike = match (variant, c) {
+        ("italic", 'h') => Some('ℎ'),
+        ("script", 'B') => Some('ℬ'),
+        ("script", 'E') => Some('ℰ'),
+        ("script", 'F') => Some('ℱ'),
+        ("script", 'H') => Some('ℋ'),
+        ("script", 'I') => Some('ℐ'),
+        ("script", 'L') => Some('ℒ'),
+        ("script", 'M') => Some('ℳ'),
+        ("script", 'R') => Some('ℛ'),
+        ("script", 'e') => Some('ℯ'),
+        ("script", 'g') => Some('ℊ'),
+        ("script", 'o') => Some('ℴ'),
+        ("fraktur", 'C') => Some('ℭ'),
+        ("fraktur", 'H') => Some('ℌ'),
+        ("fraktur", 'I') => Some('ℑ'),
+        ("fraktur", 'R') => Some('ℜ'),
+        ("fraktur", 'Z') => Some('ℨ'),
+        ("double-struck", 'C') => Some('ℂ'),
+        ("double-struck", 'H') => Some('ℍ'),
+        ("double-struck", 'N') => Some('ℕ'),
+        ("double-struck", 'P') => Some('ℙ'),
+        ("double-struck", 'Q') => Some('ℚ'),
+        ("double-struck", 'R') => Some('ℝ'),
+        ("double-struck", 'Z') => Some('ℤ'),
+        _ => None,
+    };
+    if let Some(c) = letterlike {
+        return c;
+    }
+
+    // (A, a, 0)
+    let (upper, lower, digit) = match variant {
+        "bold" => (0x1D400, 0x1D41A, Some(0x1D7CE)),
+        "italic" => (0x1D434, 0x1D44E, None),
+        "script" => (0x1D49C, 0x1D4B6, None),
+        "fraktur" => (0x1D504, 0x1D51E, None),
+        "double-struck" => (0x1D538, 0x1D552, Some(0x1D7D8)),
+        "sans-serif" => (0x1D5A0, 0x1D5BA, Some(0x1D7E2)),
+        "monospace" => (0x1D670, 0x1D68A, Some(0x1D7F6)),
+        _ => return c,
+    };
+    let code = match c {
+        'A'..='Z' => upper + (c as u32 - 'A' as u32),
+        'a'..='z' => lower + (c as u32 - 'a' as u32),
+        '0'..='9' => match digit {
+            Some(digit) => digit + (c as u32 - '0' as u32),
+            None => return c,
+        },
+        _ => return c,
+    };
+    char::from_u32(code).unwrap_or(c)
+}
+
+#[cfg(test)]
+#[test]
+fn test_latex_to_mathml() {
+    assert_eq!(
+        latex_to_mathml("x^2 + y_i", false),
+        "<math><mrow><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><msub><mi>y</mi><mi>i</mi></msub></mrow></math>"
+    );
+    assert_eq!(
+        latex_to_mathml(r"\frac{1}{2}", true),
+        r#"<math display="block"><mfrac><mn>1</mn><mn>2</mn></mfrac></math>"#
+    );
+    assert_eq!(
+        latex_to_mathml(r"\sum_{i=1}^n i", true),
+        r#"<math display="block"><mrow><munderover><mo largeop="true">∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>i</mi></mrow></math>"#
+    );
+    assert_eq!(
+        latex_to_mathml(r"\sum_{i}", false),
+        r#"<math><msub><mo largeop="true">∑</mo><mi>i</mi></msub></math>"#
+    );
+    assert_eq!(
+        latex_to_mathml(r"\sqrt[3]{\alpha} < \Omega", false),
+        r#"<math><mrow><mroot><mi>α</mi><mn>3</mn></mroot><mo>&lt;</mo><mi mathvariant="normal">Ω</mi></mrow></math>"#
+    );
+    assert_eq!(
+        latex_to_mathml(r"\left( \text{a b} \right]", false),
+        "<math><mrow><mo>(</mo><mtext>a b</mtext><mo>]</mo></mrow></math>"
+    );
+    assert_eq!(
+        latex_to_mathml(r"\begin{pmatrix} a & b \\ c & d \\ \end{pmatrix}", false),
+        "<math><mrow><mo>(</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr><mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable><mo>)</mo></mrow></math>"
+    );
+    assert_eq!(
+        latex_to_mathml(r"f'(x) = \foo", false),
+        "<math><mrow><msup><mi>f</mi><mo>′</mo></msup><mo>(</mo><mi>x</mi><mo>)</mo><mo>=</mo><merror><mtext>\\foo</mtext></merror></mrow></math>"
+    );
+    assert_eq!(
+        latex_to_mathml(r"x \in \mathbb R, \mathbf{v1}, \mathrm d", false),
+        r#"<math><mrow><mi>x</mi><mo>∈</mo><mi>ℝ</mi><mo>,</mo><mrow><mi>𝐯</mi><mn>𝟏</mn></mrow><mo>,</mo><mi mathvariant="normal">d</mi></mrow></math>"#
+    );
+    assert_eq!(
+        latex_to_mathml("3.14.", false),
+        "<math><mrow><mn>3.14</mn><mo>.</mo></mrow></math>"
+    );
+
+    // 顶层的 `\\` 和 `&` 不会丢掉后面的内容
+    assert_eq!(
+        latex_to_mathml(r"a \\ b", true),
+        r#"<math display="block"><mtable><mtr><mtd><mi>a</mi></mtd></mtr><mtr><mtd><mi>b</mi></mtd></mtr></mtable></math>"#
+    );
+    assert_eq!(
+        latex_to_mathml("x & y", false),
+        "<math><mtable><mtr><mtd><mi>x</mi></mtd><mtd><mi>y</mi></mtd></mtr></mtable></math>"
+    );
+    assert_eq!(
+        latex_to_mathml("a } b", false),
+        "<math><mrow><mi>a</mi><merror><mtext>}</mtext></merror><mi>b</mi></mrow></math>"
+    );
+
+    // 缺少参数时显示出错的部分，而不是只有一个子元素的 `<mfrac>`
+    assert_eq!(
+        latex_to_mathml(r"\frac{a}", false),
+        r"<math><merror><mtext>\frac{a}</mtext></merror></math>"
+    );
+    assert_eq!(
+        latex_to_mathml(r"{\sqrt} + \hat", false),
+        r"<math><mrow><merror><mtext>\sqrt</mtext></merror><mo>+</mo><merror><mtext>\hat</mtext></merror></mrow></math>"
+    );
+    assert_eq!(
+        latex_to_mathml("x^", false),
+        "<math><msup><mi>x</mi><merror><mtext>^</mtext></merror></msup></math>"
+    );
+    assert_eq!(
+        latex_to_mathml(r"\binom{n} & x_", false),
+        "<math><mtable><mtr><mtd><merror><mtext>\\binom{n}</mtext></merror></mtd><mtd><msub><mi>x</mi><merror><mtext>_</mtext></merror></msub></mtd></mtr></mtable></math>"
+    );
+}
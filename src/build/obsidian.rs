@@ -0,0 +1,472 @@
+//! Obsidian flavored markdown, so that a vault can be used as the posts directory
+//!
+//! `%%comments%%` are removed, `> [!note] Title` callouts are rendered as
+//! `<div class="callout">` (or `<details>` if foldable with `-`/`+`), and `![[...]]`
+//! embeds as images, audios, videos or links to the file or the note. [`Vault`]
+//! resolves the attachments by their file names like Obsidian does.
+
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, LazyLock, OnceLock},
+};
+
+use pulldown_cmark::{Event, LinkType, Tag, TagEnd};
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::build::{
+    links::{replace_url_attributes, WIKI_LINK_SCHEME},
+    utils::{escape_xml, local_ref_path, normalize_path, percent_decode},
+};
+
+/// `[!note]-  Title`
+static CALLOUT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[!([A-Za-z][\w-]*)\]([+-]?)\s*(.*)$").unwrap());
+
+/// `300` or `300x200` after the `|` of an embedded image
+static IMAGE_SIZE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(\d+)(?:x(\d+))?\s*$").unwrap());
+
+/// Remove the `%%comments%%`, which may span lines, outside of the code
+///
+/// The line breaks in the comments are kept, so are the line numbers.
+pub fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut in_comment = false;
+    // 代码块的起始标记，如 "```"
+    let mut fence: Option<String> = None;
+    for line in source.split_inclusive('\n') {
+        if !in_comment {
+            let trimmed = line.trim_start();
+            if let Some(marker) = &fence {
+                if trimmed.starts_with(marker.as_str()) {
+                    fence = None;
+                }
+                out.push_str(line);
+                continue;
+            }
+            if let Some(c) = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))
+                && trimmed.starts_with(&c.to_string().repeat(3))
+            {
+                fence = Some(trimmed.chars().take_while(|&ch| ch == c).collect());
+                out.push_str(line);
+                continue;
+            }
+        }
+
+        let mut rest = line;
+        loop {
+            if in_comment {
+                match rest.find("%%") {
+                    Some(i) => {
+                        rest = &rest[i + 2..];
+                        in_comment = false;
+                    }
+                    None => {
+                        if rest.ends_with('\n') {
+                            out.push('\n');
+                        }
+                        break;
+                    }
+                }
+            } else {
+                match comment_start(rest) {
+                    Some(i) => {
+                        out.push_str(&rest[..i]);
+                        rest = &rest[i + 2..];
+                        in_comment = true;
+                    }
+                    None => {
+                        out.push_str(rest);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Position of the first `%%` not in an inline code span
+fn comment_start(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => {
+                let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                let ticks = &line[i..i + run];
+                i += run + line[i + run..].find(ticks).map_or(0, |end| end + run);
+            }
+            b'%' if bytes.get(i + 1) == Some(&b'%') => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Render the callouts and the `![[...]]` embeds in the events of pulldown-cmark
+pub fn render_obsidian<'a>(events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    render_callouts(render_embeds(events))
+}
+
+fn render_embeds<'a>(events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut out = Vec::new();
+    // (目标, 是否有 `|`, `|` 后或默认的文本)
+    let mut embed: Option<(String, bool, String)> = None;
+    for event in events {
+        match (&mut embed, event) {
+            (
+                None,
+                Event::Start(Tag::Image {
+                    link_type: LinkType::WikiLink { has_pothole },
+                    dest_url,
+                    ..
+                }),
+            ) => embed = Some((dest_url.to_string(), has_pothole, String::new())),
+            (Some((_, _, text)), Event::Text(t) | Event::Code(t)) => text.push_str(&t),
+            (Some(_), Event::End(TagEnd::Image)) => {
+                let (target, has_pothole, text) = embed.take().unwrap();
+                out.push(Event::InlineHtml(
+                    embed_html(&target, has_pothole.then_some(text.as_str())).into(),
+                ));
+            }
+            (Some(_), _) => {}
+            (None, event) => out.push(event),
+        }
+    }
+    out
+}
+
+/// Html of `![[target|text]]`
+fn embed_html(target: &str, text: Option<&str>) -> String {
+    let path = target.split('#').next().unwrap_or_default();
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let extension = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let src = escape_xml(path);
+    match extension.as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "avif" | "bmp" => {
+            let size = text.and_then(|text| IMAGE_SIZE.captures(text));
+            let alt = match (&size, text) {
+                (None, Some(text)) => text,
+                _ => name,
+            };
+            let size = size.map_or(String::new(), |size| match size.get(2) {
+                Some(height) => format!(r#" width="{}" height="{}""#, &size[1], height.as_str()),
+                None => format!(r#" width="{}""#, &size[1]),
+            });
+            format!(r#"<img src="{src}" alt="{}"{size}>"#, escape_xml(alt))
+        }
+        "mp3" | "wav" | "ogg" | "m4a" | "flac" => {
+            format!(r#"<audio controls src="{src}"></audio>"#)
+        }
+        "mp4" | "webm" | "mov" | "mkv" => format!(r#"<video controls src="{src}"></video>"#),
+        // 嵌入其他笔记时只链接到它
+        "" | "md" => format!(
+            r#"<a href="{}">{}</a>"#,
+            escape_xml(&format!("{WIKI_LINK_SCHEME}{target}")),
+            escape_xml(text.unwrap_or(target))
+        ),
+        _ => format!(
+            r#"<a href="{src}">{}</a>"#,
+            escape_xml(text.unwrap_or(name))
+        ),
+    }
+}
+
+fn render_callouts(events: Vec<Event<'_>>) -> Vec<Event<'_>> {
+    let mut out = Vec::with_capacity(events.len());
+    // 每层 blockquote 的结束标签，不是 callout 时为 None
+    let mut ends: Vec<Option<&str>> = Vec::new();
+    let mut i = 0;
+    while i < events.len() {
+        match &events[i] {
+            Event::Start(Tag::BlockQuote(_)) => {
+                let Some((mut callout, header_len)) = parse_callout(&events[i + 1..]) else {
+                    ends.push(None);
+                    out.push(events[i].clone());
+                    i += 1;
+                    continue;
+                };
+                // 跳过 `<p>` 和 `[!type]` 所在的文本，标题的其余部分可以有格式
+                i += 1 + header_len;
+                let title_len = events[i..]
+                    .iter()
+                    .take_while(|event| {
+                        !matches!(
+                            event,
+                            Event::SoftBreak | Event::HardBreak | Event::End(TagEnd::Paragraph)
+                        )
+                    })
+                    .count();
+                if callout.title.is_empty() && title_len == 0 {
+                    callout.title = capitalize(&callout.kind);
+                }
+                out.push(Event::Html(callout.open_html().into()));
+                out.extend(events[i..i + title_len].iter().cloned());
+                out.push(Event::Html(callout.content_html().into()));
+                ends.push(Some(callout.close_html()));
+                i += title_len;
+                match events.get(i) {
+                    Some(Event::End(TagEnd::Paragraph)) => i += 1,
+                    _ => {
+                        out.push(Event::Start(Tag::Paragraph));
+                        i += 1;
+                    }
+                }
+            }
+            Event::End(TagEnd::BlockQuote(_)) => {
+                match ends.pop().flatten() {
+                    Some(end) => out.push(Event::Html(end.into())),
+                    None => out.push(events[i].clone()),
+                }
+                i += 1;
+            }
+            event => {
+                out.push(event.clone());
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+struct Callout {
+    kind: String,
+    /// The plain text of the title on the line of `[!type]`
+    title: String,
+    /// `Some(open)` if it can be folded
+    fold: Option<bool>,
+}
+
+impl Callout {
+    /// Up to the title
+    fn open_html(&self) -> String {
+        let kind = escape_xml(&self.kind);
+        let title = escape_xml(&self.title);
+        match self.fold {
+            Some(open) => format!(
+                r#"<details class="callout" data-callout="{kind}"{}><summary class="callout-title">{title}"#,
+                if open { " open" } else { "" }
+            ),
+            None => format!(
+                r#"<div class="callout" data-callout="{kind}"><div class="callout-title">{title}"#
+            ),
+        }
+    }
+
+    /// After the title, up to the content
+    fn content_html(&self) -> &'static str {
+        match self.fold {
+            Some(_) => r#"</summary><div class="callout-content">"#,
+            None => r#"</div><div class="callout-content">"#,
+        }
+    }
+
+    fn close_html(&self) -> &'static str {
+        match self.fold {
+            Some(_) => "</div></details>\n",
+            None => "</div></div>\n",
+        }
+    }
+}
+
+/// The callout header at the start of the events in a blockquote, and the number of
+/// the events of it, which are `<p>` and the texts containing `[!type]`
+fn parse_callout(events: &[Event<'_>]) -> Option<(Callout, usize)> {
+    let Some(Event::Start(Tag::Paragraph)) = events.first() else {
+        return None;
+    };
+    // `[` 和 `]` 可能被拆成单独的文本
+    let texts = events[1..]
+        .iter()
+        .map_while(|event| match event {
+            Event::Text(text) => Some(text.as_ref()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let header = texts.concat();
+    let caps = CALLOUT.captures(&header)?;
+
+    let fold = match &caps[2] {
+        "-" => Some(false),
+        "+" => Some(true),
+        _ => None,
+    };
+    let callout = Callout {
+        kind: caps[1].to_lowercase(),
+        title: caps[3].trim_start().to_string(),
+        fold,
+    };
+    Some((callout, 1 + texts.len()))
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// The files of an Obsidian vault by their names, to resolve the attachments
+///
+/// The files are indexed on first use, hidden directories like `.obsidian` are skipped,
+/// and the first file in the order of the paths wins if several have the same name.
+#[derive(Debug, Clone)]
+pub struct Vault {
+    root: PathBuf,
+    files: Arc<OnceLock<HashMap<String, PathBuf>>>,
+}
+
+impl Vault {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            files: Arc::default(),
+        }
+    }
+
+    fn files(&self) -> &HashMap<String, PathBuf> {
+        self.files.get_or_init(|| {
+            let mut files = HashMap::new();
+            let entries = WalkDir::new(&self.root)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|entry| !crate::build::is_hidden(entry))
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_file());
+            for entry in entries {
+                let name = entry.file_name().to_string_lossy().to_string();
+                files
+                    .entry(name)
+                    .or_insert_with(|| normalize_path(entry.path()));
+            }
+            files
+        })
+    }
+
+    /// Rewrite the local urls in `html` of the file at `path`, which do not exist
+    /// relative to it, to the files with the same names in the vault
+    pub fn resolve_attachments(&self, html: &str, path: &Path) -> String {
+        let dir = normalize_path(path.parent().unwrap_or(Path::new("")));
+        let resolve = |url: &str| -> Option<String> {
+            let target = local_ref_path(url)?;
+            let decoded = percent_decode(&target.replace("&amp;", "&"));
+            if dir.join(&decoded).exists() {
+                return None;
+            }
+            let name = Path::new(&decoded).file_name()?.to_string_lossy();
+            let file = self.files().get(name.as_ref())?;
+            let suffix = &url.trim()[target.len()..];
+            Some(format!("{}{suffix}", relative_path(file, &dir)))
+        };
+
+        replace_url_attributes(html, |attribute| match attribute.name {
+            // `a.png 1x, b.png 2x`
+            "srcset" => Some(
+                attribute
+                    .value
+                    .split(',')
+                    .map(|candidate| {
+                        let candidate = candidate.trim();
+                        let (url, descriptor) =
+                            candidate.split_once(' ').unwrap_or((candidate, ""));
+                        let url = resolve(url).unwrap_or(url.to_string());
+                        format!("{url} {descriptor}").trim().to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            _ => resolve(attribute.value).map(|url| escape_xml(&url)),
+        })
+    }
+}
+
+/// `target` relative to the directory `base`, both normalized, like `../assets/a.png`
+fn relative_path(target: &Path, base: &Path) -> String {
+    let target = target.components().collect::<Vec<_>>();
+    let base = base.components().collect::<Vec<_>>();
+    let common = target.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    base[common..]
+        .iter()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .map(|_| "..".to_string())
+        .chain(
+            target[common..]
+                .iter()
+                .map(|c| c.as_os_str().to_string_lossy().to_string()),
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+#[test]
+fn test_obsidian_markdown() {
+    use crate::build::markdown::{to_html, MarkdownOptions};
+
+    let source = "\
+Visible %%hidden%% text `%%code%%`
+
+%%
+block comment
+%%
+
+> [!tip]- Read *this*
+> Folded content
+
+> [!info]+
+
+> [!warning]
+> Be careful
+
+> plain quote
+
+![[avatar.png|200]] ![[song.mp3]] ![[doc.pdf]] ![[Other Note]]
+";
+    let html = to_html(source, &MarkdownOptions::default().with_obsidian(true));
+    assert!(html.contains("<p>Visible  text <code>%%code%%</code></p>"));
+    assert!(!html.contains("block comment"));
+    assert!(html.contains(
+        r#"<details class="callout" data-callout="tip"><summary class="callout-title">Read <em>this</em></summary><div class="callout-content">
+<p>Folded content</p>"#
+    ));
+    assert!(html.contains(
+        r#"<details class="callout" data-callout="info" open><summary class="callout-title">Info</summary><div class="callout-content"></div></details>"#
+    ));
+    assert!(html.contains(
+        r#"<div class="callout" data-callout="warning"><div class="callout-title">Warning</div><div class="callout-content">
+<p>Be careful</p>
+</div></div>"#
+    ));
+    assert!(html.contains("<blockquote>\n<p>plain quote</p>\n</blockquote>"));
+    assert!(html.contains(r#"<img src="avatar.png" alt="avatar.png" width="200">"#));
+    assert!(html.contains(r#"<audio controls src="song.mp3"></audio>"#));
+    assert!(html.contains(r#"<a href="doc.pdf">doc.pdf</a>"#));
+    assert!(html.contains(r#"<a href="wiki:Other Note">Other Note</a>"#));
+}
+
+#[cfg(test)]
+#[test]
+fn test_vault_attachments() {
+    let dir = crate::build::utils::temp_dir_with(&[
+        (".obsidian/avatar.png", ""),
+        ("attachments/avatar.png", ""),
+        ("notes/local.png", ""),
+    ]);
+
+    let vault = Vault::new(dir.path());
+    let html = vault.resolve_attachments(
+        r#"<img src="avatar.png"><img src="local.png"><a href="https://example.com">x</a>"#,
+        &dir.path().join("notes/note.md"),
+    );
+    assert_eq!(
+        html,
+        r#"<img src="../attachments/avatar.png"><img src="local.png"><a href="https://example.com">x</a>"#
+    );
+}
@@ -0,0 +1,262 @@
+use std::{collections::HashMap, io::Write};
+
+use orgize::{
+    elements::Element,
+    export::{DefaultHtmlHandler, HtmlHandler},
+    Org,
+};
+
+use crate::build::{
+    frontmatter::FrontMatter, highlight, post::Post, utils::escape_xml, Entity, Parser,
+};
+
+/// Parses Org-mode posts
+///
+/// The `#+KEYWORD: value` lines are the frontmatter of the post, see
+/// [`FrontMatter::from_attributes`], and `#+TITLE` becomes the `<h1>` of the post,
+/// so the top level headlines are rendered as `<h2>`.
+#[derive(Debug, Clone, Default)]
+pub struct OrgPostParser;
+
+impl Parser for OrgPostParser {
+    type Output = Post;
+    fn try_parse(&self, entity: Entity) -> Result<Self::Output, anyhow::Error> {
+        let source = std::str::from_utf8(&entity.content)?;
+        let (source, definitions) = split_footnote_definitions(source);
+        let org = Org::parse(&source);
+        let meta = FrontMatter::from_attributes(
+            org.keywords()
+                .map(|keyword| (keyword.key.as_ref(), keyword.value.as_ref())),
+        )?;
+
+        let mut html = Vec::new();
+        if let Some(title) = &meta.title {
+            write!(html, "<h1>{}</h1>", escape_xml(title))?;
+        }
+        let mut handler = OrgHtmlHandler::default();
+        org.write_html_custom(&mut html, &mut handler)?;
+        for (label, definition) in definitions {
+            let mut definition_html = Vec::new();
+            Org::parse(&definition)
+                .write_html_custom(&mut definition_html, &mut OrgHtmlHandler::default())?;
+            handler.define_footnote(label, String::from_utf8(definition_html)?);
+        }
+        html.extend(handler.footnotes().into_bytes());
+
+        Ok(Post::from_html_entity_with_meta(
+            String::from_utf8(html)?,
+            entity,
+            meta,
+        ))
+    }
+}
+
+/// Take the `[fn:label] definition` paragraphs out of the source
+///
+/// orgize 0.9 fails its own validation on footnote definitions and panics in debug
+/// builds, which build scripts are by default, so they are rendered separately. The
+/// lines inside the `#+BEGIN_...` blocks are left as they are.
+fn split_footnote_definitions(source: &str) -> (String, Vec<(String, String)>) {
+    let mut body = String::new();
+    let mut definitions: Vec<(String, String)> = Vec::new();
+    let mut in_definition = false;
+    let mut in_block = false;
+    for line in source.lines() {
+        let directive = line.trim_start().to_lowercase();
+        if in_block || directive.starts_with("#+begin_") {
+            in_block = !directive.starts_with("#+end_");
+            in_definition = false;
+            body.push_str(line);
+            body.push('\n');
+            continue;
+        }
+        let definition = line
+            .strip_prefix("[fn:")
+            .and_then(|rest| rest.split_once(']'))
+            .filter(|(label, _)| !label.is_empty() && !label.contains(':'));
+        if let Some((label, text)) = definition {
+            definitions.push((label.to_string(), text.trim().to_string()));
+            in_definition = true;
+        } else if in_definition && !line.trim().is_empty() && !line.starts_with('*') {
+            let (_, text) = definitions.last_mut().unwrap();
+            text.push('\n');
+            text.push_str(line);
+        } else {
+            in_definition = false;
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    (body, definitions)
+}
+
+/// [`DefaultHtmlHandler`] with the output aligned with the markdown posts
+#[derive(Default)]
+struct OrgHtmlHandler {
+    inner: DefaultHtmlHandler,
+    /// Footnote label -> number, in the order of the references
+    footnote_numbers: HashMap<String, usize>,
+    /// Footnote label -> html of the definition
+    footnote_definitions: HashMap<String, String>,
+}
+
+impl OrgHtmlHandler {
+    fn footnote_number(&mut self, label: &str) -> usize {
+        let next = self.footnote_numbers.len() + 1;
+        *self
+            .footnote_numbers
+            .entry(label.to_string())
+            .or_insert(next)
+    }
+
+    fn define_footnote(&mut self, label: String, html: String) {
+        self.footnote_definitions.insert(label, html);
+    }
+
+    /// The footnote definitions, in the order of the references
+    fn footnotes(&mut self) -> String {
+        let mut definitions = std::mem::take(&mut self.footnote_definitions)
+            .into_iter()
+            .map(|(label, html)| (self.footnote_number(&label), label, html))
+            .collect::<Vec<_>>();
+        definitions.sort();
+        definitions
+            .into_iter()
+            .map(|(n, label, html)| {
+                format!(
+                    r#"<div class="footnote-definition" id="{}"><sup class="footnote-definition-label">{n}</sup>{html}</div>"#,
+                    escape_xml(&label)
+                )
+            })
+            .collect()
+    }
+}
+
+fn heading_level(level: usize) -> usize {
+    (level + 1).min(6)
+}
+
+fn is_image(path: &str) -> bool {
+    let ext = path.rsplit('.').next().unwrap_or_default().to_lowercase();
+    matches!(
+        ext.as_str(),
+        "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "avif"
+    )
+}
+
+impl HtmlHandler<std::io::Error> for OrgHtmlHandler {
+    fn start<W: Write>(&mut self, mut w: W, element: &Element) -> std::io::Result<()> {
+        match element {
+            // 不需要 `<main>` 和 `<section>` 包裹
+            Element::Document { .. } | Element::Section => {}
+            Element::Bold => write!(w, "<strong>")?,
+            Element::Italic => write!(w, "<em>")?,
+            Element::Strike => write!(w, "<del>")?,
+            Element::Title(title) => write!(w, "<h{}>", heading_level(title.level))?,
+            Element::SourceBlock(block) if !block.language.is_empty() => {
+                let lang = block.language.as_ref();
+                let html = highlight::highlight_code(&block.contents, lang)
+                    .unwrap_or_else(|| escape_xml(&block.contents));
+                write!(
+                    w,
+                    r#"<pre><code class="language-{}">{html}</code></pre>"#,
+                    escape_xml(lang)
+                )?
+            }
+            Element::SourceBlock(block) => {
+                write!(w, "<pre><code>{}</code></pre>", escape_xml(&block.contents))?
+            }
+            Element::Link(link) => {
+                let path = link.path.strip_prefix("file:").unwrap_or(&link.path);
+                match &link.desc {
+                    None if is_image(path) => {
+                        let alt = path.rsplit('/').next().unwrap_or(path);
+                        write!(
+                            w,
+                            r#"<img src="{}" alt="{}">"#,
+                            escape_xml(path),
+                            escape_xml(alt)
+                        )?
+                    }
+                    desc => write!(
+                        w,
+                        r#"<a href="{}">{}</a>"#,
+                        escape_xml(path),
+                        escape_xml(desc.as_deref().unwrap_or(path))
+                    )?,
+                }
+            }
+            Element::FnRef(fn_ref) => {
+                let label = fn_ref.label.to_string();
+                let n = self.footnote_number(&label);
+                if let Some(definition) = &fn_ref.definition {
+                    let html = format!("<p>{}</p>", escape_xml(definition));
+                    self.define_footnote(label.clone(), html);
+                }
+                write!(
+                    w,
+                    r##"<sup class="footnote-reference"><a href="#{}">{n}</a></sup>"##,
+                    escape_xml(&label)
+                )?
+            }
+            element => self.inner.start(w, element)?,
+        }
+        Ok(())
+    }
+
+    fn end<W: Write>(&mut self, mut w: W, element: &Element) -> std::io::Result<()> {
+        match element {
+            Element::Document { .. } | Element::Section => {}
+            Element::Bold => write!(w, "</strong>")?,
+            Element::Italic => write!(w, "</em>")?,
+            Element::Strike => write!(w, "</del>")?,
+            Element::Title(title) => write!(w, "</h{}>", heading_level(title.level))?,
+            element => self.inner.end(w, element)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_org_parser() {
+    let dir = crate::build::utils::temp_dir_with(&[(
+        "notes.org",
+        r#"#+TITLE: Org Notes
+#+DATE: <2024-01-02 Tue>
+#+FILETAGS: :emacs:notes:
+
+* Intro
+Some *bold* and /italic/ text.[fn:1]
+
+[[file:assets/avatar.jpg]]
+
+#+BEGIN_SRC rust
+fn main() {}
+[fn:2] not a footnote
+#+END_SRC
+
+[fn:1] A footnote
+in two lines.
+"#,
+    )]);
+    let path = dir.path().join("notes.org");
+
+    let post = OrgPostParser
+        .try_parse(Entity::new(&path).unwrap())
+        .unwrap();
+    assert_eq!(post.title, "Org Notes");
+    assert_eq!(post.meta.date.unwrap().unix_timestamp(), 1704153600);
+    assert_eq!(post.meta.tags, vec!["emacs", "notes"]);
+    let html = &post.content_html;
+    assert!(html.starts_with("<h1"));
+    assert!(html.contains("Intro</h2>"));
+    assert!(html.contains("Some <strong>bold</strong> and <em>italic</em> text."));
+    assert!(html.contains(r#"<img alt="avatar.jpg" src="assets/avatar.jpg">"#));
+    assert!(html.contains(r#"<pre><code class="language-rust"><span class="hl-source hl-rust">"#));
+    assert!(html.contains(r#"<div class="footnote-definition" id="1"><sup class="footnote-definition-label">1</sup><p>A footnote
+in two lines.</p></div>"#));
+    assert!(html.contains("</span> not a footnote"));
+    assert!(!html.contains(r#"id="2""#));
+    assert_eq!(post.ref_paths, vec!["assets/avatar.jpg"]);
+}
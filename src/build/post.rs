@@ -1,16 +1,34 @@
-use std::{ops::Deref, path::Path};
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context;
 
-use crate::build::{utils, Entity, Parser};
+use crate::{
+    build::{
+        frontmatter::{split_frontmatter, FrontMatter},
+        markdown::{self, MarkdownOptions},
+        obsidian::Vault,
+        section,
+        summary::{self, SummaryStrategy},
+        toc, utils, Entity, ParseOptions, Parser,
+    },
+    TocEntry,
+};
 
 #[derive(Debug, Clone)]
 pub struct Post {
     pub entity: Entity,
+    pub meta: FrontMatter,
     pub ref_paths: Vec<String>,
     pub title: String,
     pub summary_html: String,
     pub content_html: String,
+    pub toc: Vec<TocEntry>,
+    /// Slugs of the posts linking to this one, filled by
+    /// [`resolve_links`](crate::build::links::resolve_links)
+    pub backlinks: Vec<String>,
 }
 
 impl Deref for Post {
@@ -22,83 +40,270 @@ impl Deref for Post {
 
 impl Post {
     pub fn from_html_entity(content_html: String, entity: Entity) -> Self {
-        let title =
-            utils::get_tag_content(&content_html, "h1").unwrap_or(entity.base_name().clone());
-        let filtered_html = utils::remove_html_tag(&content_html, &["h1"]);
-        let summary_html = utils::extract_html_summary(&filtered_html, 200);
+        Self::from_html_entity_with_meta(content_html, entity, FrontMatter::default())
+    }
+
+    /// Same as [`Post::from_html_entity`], but values in `meta` take precedence
+    /// over the ones derived from the html and git
+    pub fn from_html_entity_with_meta(
+        content_html: String,
+        mut entity: Entity,
+        meta: FrontMatter,
+    ) -> Self {
+        let (content_html, toc) = toc::add_heading_ids(&content_html);
+        let title = meta
+            .title
+            .clone()
+            .or_else(|| utils::get_html_h1(&content_html))
+            .unwrap_or(entity.base_name().clone());
+        let summary_html = summary::summary_html(
+            &content_html,
+            meta.summary.as_deref(),
+            SummaryStrategy::default(),
+        );
+        if let Some(date) = meta.date {
+            entity.created = date;
+            entity.updated = meta.updated.unwrap_or(entity.updated.max(date));
+        } else if let Some(updated) = meta.updated {
+            entity.updated = updated;
+        }
 
         Self {
             entity,
+            meta,
             ref_paths: utils::get_ref_paths(&content_html),
             title,
             summary_html,
             content_html,
+            toc,
+            backlinks: vec![],
+        }
+    }
+
+    /// Re-extract `summary_html` with `strategy`, an explicit summary still takes precedence
+    pub fn summarize(&mut self, strategy: SummaryStrategy) {
+        self.summary_html =
+            summary::summary_html(&self.content_html, self.meta.summary.as_deref(), strategy);
+    }
+
+    /// The slug of the post, the frontmatter `slug` overrides the one from the file name
+    ///
+    /// Posts in a section are prefixed with the slug of the section, like `rust/async-md`.
+    pub fn slug(&self) -> String {
+        let slug = self.meta.slug.clone().unwrap_or_else(|| self.entity.slug());
+        if self.entity.section.is_empty() {
+            slug
+        } else {
+            format!("{}/{slug}", section::section_slug(&self.entity.section))
+        }
+    }
+
+    /// The frontmatter `aliases` as slugs, `/posts/old-name/` and `old-name` are both
+    /// `old-name`, the ones same as [`Post::slug`] are dropped
+    pub fn aliases(&self) -> Vec<String> {
+        let slug = self.slug();
+        let mut aliases = Vec::new();
+        for alias in &self.meta.aliases {
+            let alias = alias.trim().trim_matches('/');
+            let alias = alias.strip_prefix("posts/").unwrap_or(alias).to_string();
+            if !alias.is_empty() && alias != slug && !aliases.contains(&alias) {
+                aliases.push(alias);
+            }
         }
+        aliases
+    }
+}
+
+impl Post {
+    /// Parse `entity` with the parser registered for its extension in `options`
+    pub fn parse(entity: Entity, options: &ParseOptions) -> Result<Self, anyhow::Error> {
+        let mut post = options.parsers.parse(entity)?;
+        post.summarize(options.summary);
+        Ok(post)
     }
 }
 
 impl TryFrom<Entity> for Post {
     type Error = anyhow::Error;
     fn try_from(entity: Entity) -> Result<Self, Self::Error> {
-        match entity.extension().as_str() {
-            "md" => MarkdownPostParser::try_parse(entity),
-            "typ" => TypstPostParser::try_parse(entity),
-            _ => anyhow::bail!("unsupported file extension: {}", entity.extension()),
-        }
+        Self::parse(entity, &ParseOptions::default())
     }
 }
 
-pub struct TypstPostParser;
+#[derive(Debug, Clone, Default)]
+pub struct TypstPostParser {
+    root: Option<PathBuf>,
+}
+
+impl TypstPostParser {
+    /// Only the files under `root` can be read by the posts, like the doc-src directory,
+    /// default is the posts directory passed to [`crate::build::parse_posts`], or the
+    /// directory containing the post if it is parsed on its own
+    pub fn with_root(mut self, root: impl AsRef<Path>) -> Self {
+        self.root = Some(root.as_ref().to_path_buf());
+        self
+    }
+}
 
 impl Parser for TypstPostParser {
     type Output = Post;
-    fn try_parse(entity: Entity) -> Result<Self::Output, anyhow::Error> {
-        let content_html = compile_typst_to_html(&entity.path)?;
-        let content_html = utils::get_tag_content(&content_html, "body").expect("no body");
+    fn try_parse(&self, entity: Entity) -> Result<Self::Output, anyhow::Error> {
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            // 各级分区目录之上就是 `parse_posts` 的目录
+            None => entity
+                .path
+                .ancestors()
+                .nth(entity.section.len() + 1)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."))
+                .to_path_buf(),
+        };
+        let content_html = compile_typst_to_html(&entity.path, root)
+            .with_context(|| format!("failed to compile {}", entity.path.display()))?;
+        let content_html =
+            utils::get_tag_content(&content_html, "body").context("no body in typst output")?;
         let content_html = utils::remove_html_tag(&content_html, &["h1"]);
         Ok(Post::from_html_entity(content_html, entity))
     }
 }
 
-fn compile_typst_to_html(path: impl AsRef<Path>) -> Result<String, anyhow::Error> {
-    let child = std::process::Command::new("typst")
+/// Compile in-process with the embedded typst compiler
+#[cfg(feature = "typst")]
+fn compile_typst_to_html(
+    path: impl AsRef<Path>,
+    root: impl AsRef<Path>,
+) -> Result<String, anyhow::Error> {
+    crate::build::typst::compile_to_html(path, root)
+}
+
+/// Compile with the `typst` cli, which must be installed
+#[cfg(not(feature = "typst"))]
+fn compile_typst_to_html(
+    path: impl AsRef<Path>,
+    root: impl AsRef<Path>,
+) -> Result<String, anyhow::Error> {
+    let output = std::process::Command::new("typst")
         .arg("compile")
+        .arg("--root")
+        .arg(root.as_ref())
         .arg(path.as_ref())
         .arg("-")
         .arg("-fhtml")
         .args(["--features", "html"])
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .context("failed to spawn typst")?;
+        .output()
+        .context("failed to spawn typst, is it installed? or enable the `typst` feature")?;
 
-    let output = child
-        .wait_with_output()
-        .context("failed to wait for typst")?
-        .stdout;
-    String::from_utf8(output).context("contains invalid utf-8 content")
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        anyhow::bail!(
+            "typst exited with {}:\n{}",
+            output.status,
+            stderr.trim_end()
+        );
+    }
+    for line in stderr.lines().filter(|l| !l.trim().is_empty()) {
+        println!("cargo:warning={line}");
+    }
+    String::from_utf8(output.stdout).context("contains invalid utf-8 content")
 }
 
-pub struct MarkdownPostParser;
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownPostParser {
+    options: MarkdownOptions,
+    vault: Option<Vault>,
+}
+
+impl MarkdownPostParser {
+    pub fn new(options: MarkdownOptions) -> Self {
+        Self {
+            options,
+            vault: None,
+        }
+    }
+    /// Resolve the attachments not found next to the posts by their names in the
+    /// Obsidian vault at `root`, see [`Vault`]
+    pub fn with_vault(mut self, root: impl AsRef<Path>) -> Self {
+        self.vault = Some(Vault::new(root));
+        self
+    }
+}
 
 impl Parser for MarkdownPostParser {
     type Output = Post;
-    fn try_parse(entity: Entity) -> Result<Self::Output, anyhow::Error> {
+    fn try_parse(&self, entity: Entity) -> Result<Self::Output, anyhow::Error> {
         let content = std::str::from_utf8(&entity.content)?;
+        let (meta, content) = split_frontmatter(content)?;
 
-        let parser = pulldown_cmark::Parser::new(&content);
-        let mut content_html = String::new();
-        pulldown_cmark::html::push_html(&mut content_html, parser);
+        let mut content_html = markdown::to_html(content, &self.options);
+        if let Some(vault) = &self.vault {
+            content_html = vault.resolve_attachments(&content_html, &entity.path);
+        }
 
-        Ok(Post::from_html_entity(content_html, entity))
+        Ok(Post::from_html_entity_with_meta(content_html, entity, meta))
     }
 }
 
+/// A post of `html` at `path`, without reading the file
+#[cfg(test)]
+pub(crate) fn test_post(path: &str, html: &str) -> Post {
+    let entity = Entity {
+        path: path.into(),
+        created: time::UtcDateTime::UNIX_EPOCH,
+        updated: time::UtcDateTime::UNIX_EPOCH,
+        uncommitted: false,
+        content: vec![],
+        section: vec![],
+    };
+    Post::from_html_entity(html.to_string(), entity)
+}
+
 #[cfg(test)]
 #[test]
 fn test_compile_typst_to_html_basic() {
-    let _result = compile_typst_to_html("example/sycamore/doc-src/posts/typst.typ").unwrap();
+    let _result = compile_typst_to_html(
+        "example/sycamore/doc-src/posts/typst.typ",
+        "example/sycamore/doc-src",
+    )
+    .unwrap();
     let _result = utils::get_tag_content(&_result, "body").unwrap();
     let _result = utils::remove_html_tag(&_result, &["h1"]);
 
     // println!("{}", _result)
 }
+
+#[cfg(all(test, feature = "typst"))]
+#[test]
+fn test_typst_default_root() {
+    let dir = utils::temp_dir_with(&[
+        ("posts/rust/a.typ", "= A\n\n#read(\"/data.txt\")\n"),
+        ("posts/data.txt", "shared"),
+    ]);
+
+    let report =
+        crate::build::parse_posts_report(dir.path().join("posts"), &ParseOptions::default());
+    assert_eq!(report.errors().count(), 0);
+    assert!(report.posts[0].content_html.contains("shared"));
+}
+
+#[cfg(test)]
+#[test]
+fn test_post_parsers() {
+    use crate::build::PostParsers;
+
+    let dir = utils::temp_dir_with(&[("note.txt", "plain <text>")]);
+    let path = dir.path().join("note.txt");
+
+    let entity = Entity::new(&path).unwrap();
+    assert!(PostParsers::default().parse(entity.clone()).is_err());
+
+    let parsers = PostParsers::default().register("txt", |entity: Entity| {
+        let text = std::str::from_utf8(&entity.content)?;
+        let html = format!("<p>{}</p>", utils::escape_xml(text));
+        Ok(Post::from_html_entity(html, entity))
+    });
+    let options = ParseOptions::default().with_parsers(parsers);
+    let post = Post::parse(entity, &options).unwrap();
+    assert_eq!(post.content_html, "<p>plain &lt;text&gt;</p>");
+    assert_eq!(post.title, "note");
+}
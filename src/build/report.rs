@@ -0,0 +1,206 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::build::post::Post;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The file is dropped from the site, or has broken links
+    Error,
+    /// The file is skipped or built with something missing
+    Warning,
+}
+
+/// A problem found when building a file
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub path: PathBuf,
+    /// 1-based line in the file, if the problem is at a specific place
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: {}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The posts built from a directory, along with the errors and warnings of the files
+#[derive(Debug, Clone, Default)]
+pub struct BuildReport {
+    pub posts: Vec<Post>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl BuildReport {
+    pub fn error(&mut self, path: impl AsRef<Path>, err: &anyhow::Error) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            path: path.as_ref().to_path_buf(),
+            line: None,
+            // `{:#}` 会带上 context 链
+            message: format!("{err:#}"),
+        });
+    }
+
+    /// An error at `line` of the file, which is still built
+    pub fn error_at(
+        &mut self,
+        path: impl AsRef<Path>,
+        line: Option<usize>,
+        message: impl Into<String>,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            path: path.as_ref().to_path_buf(),
+            line,
+            message: message.into(),
+        });
+    }
+
+    pub fn warning(&mut self, path: impl AsRef<Path>, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            path: path.as_ref().to_path_buf(),
+            line: None,
+            message: message.into(),
+        });
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Print the diagnostics as `cargo:warning` lines, shown by cargo when building
+    pub fn emit(&self) {
+        for diagnostic in &self.diagnostics {
+            for line in diagnostic.to_string().lines() {
+                println!("cargo:warning={line}");
+            }
+        }
+    }
+
+    /// The posts, or an error listing all the failed files if there is any
+    pub fn into_strict(self) -> Result<Vec<Post>, anyhow::Error> {
+        if self.has_errors() {
+            let errors = self.errors().map(|e| e.to_string()).collect::<Vec<_>>();
+            anyhow::bail!(
+                "{} file(s) failed to build:\n{}",
+                errors.len(),
+                errors.join("\n")
+            );
+        }
+        Ok(self.posts)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_posts_report() {
+    let dir = crate::build::utils::temp_dir_with(&[
+        ("ok.md", "# Ok\n"),
+        ("draft.md", "---\ndraft: true\n---\n# Draft\n"),
+        ("image.png", ""),
+        ("notes.txt", ""),
+    ]);
+    std::fs::write(dir.path().join("bad.md"), b"\xff\xfe").unwrap();
+
+    let options = crate::build::ParseOptions::default();
+    let report = crate::build::parse_posts_report(dir.path(), &options);
+    assert_eq!(report.posts.len(), 1);
+    let errors = report.errors().collect::<Vec<_>>();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].path.ends_with("bad.md"));
+    assert!(errors[0].message.contains("utf-8"));
+    let warnings = report.warnings().collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].path.ends_with("notes.txt"));
+
+    assert!(report.into_strict().is_err());
+
+    let options = crate::build::ParseOptions::default().with_drafts(true);
+    let report = crate::build::parse_posts_report(dir.path(), &options);
+    assert_eq!(report.posts.len(), 2);
+}
+
+#[cfg(test)]
+#[test]
+fn test_slug_collisions() {
+    let dir = crate::build::utils::temp_dir_with(&[
+        ("a b.md", "# Space\n"),
+        ("a-b.md", "# Dash\n"),
+        ("new.md", "---\naliases: [/posts/a-b-md/]\n---\n# New\n"),
+        (
+            "renamed.md",
+            "---\nslug: stable\naliases: [old]\n---\n# Renamed\n",
+        ),
+    ]);
+
+    let report =
+        crate::build::parse_posts_report(dir.path(), &crate::build::ParseOptions::default());
+    let slugs = report.posts.iter().map(|p| p.slug()).collect::<Vec<_>>();
+    assert_eq!(slugs, vec!["a-b-md", "stable"]);
+    assert_eq!(report.posts[1].aliases(), vec!["old"]);
+
+    let errors = report.errors().collect::<Vec<_>>();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().any(|e| e.path.ends_with("a-b.md")
+        && e.message.contains("slug `a-b-md`")
+        && e.message.contains("a b.md")));
+    assert!(errors.iter().any(|e| e.path.ends_with("new.md")
+        && e.message.contains("alias `a-b-md`")
+        && e.message.contains("a b.md")));
+}
+
+#[cfg(test)]
+#[test]
+fn test_invalid_slugs() {
+    let dir = crate::build::utils::temp_dir_with(&[
+        ("rust/async.md", "# Async\n"),
+        ("docs/_index.md", "# Docs\n"),
+        ("clash.md", "---\nslug: rust\n---\n# Clash\n"),
+        ("index-clash.md", "---\naliases: [docs]\n---\n# Clash\n"),
+        ("escape.md", "---\nslug: ../../etc\n---\n# Escape\n"),
+        ("blank.md", "---\naliases: [\"a/ /b\"]\n---\n# Blank\n"),
+    ]);
+
+    let report =
+        crate::build::parse_posts_report(dir.path(), &crate::build::ParseOptions::default());
+    let slugs = report.posts.iter().map(|p| p.slug()).collect::<Vec<_>>();
+    assert_eq!(slugs, vec!["rust/async-md"]);
+
+    let errors = report
+        .errors()
+        .map(|e| (e.path.file_name().unwrap().to_str().unwrap(), &e.message))
+        .collect::<Vec<_>>();
+    assert_eq!(errors.len(), 4);
+    let error = |file: &str| errors.iter().find(|(f, _)| *f == file).unwrap().1;
+    assert!(error("clash.md").contains("slug `rust` is already used by"));
+    assert!(error("index-clash.md").contains("alias `docs` is already used by"));
+    assert!(error("escape.md").contains("slug `../../etc` has a `.` or `..` segment"));
+    assert!(error("blank.md").contains("alias `a/ /b` has an empty segment"));
+}
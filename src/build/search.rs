@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+
+use crate::{
+    build::{post::Post, utils},
+    search::tokenize,
+};
+
+/// Occurrences in the title count this many times as much as the ones in the body
+const TITLE_WEIGHT: u32 = 5;
+
+/// The build-time representation of [`crate::search::SearchIndex`]
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndexData {
+    /// `(slug, title, text, len)`
+    pub docs: Vec<(String, String, String, u32)>,
+    pub terms: BTreeMap<String, Vec<(u32, u32)>>,
+}
+
+/// Build the inverted index
+///
+/// `entries` yields `(slug, title, content_html)` of each post.
+pub fn build_search_index<'a>(
+    entries: impl IntoIterator<Item = (String, &'a str, &'a str)>,
+) -> SearchIndexData {
+    let mut index = SearchIndexData::default();
+    for (doc, (slug, title, content_html)) in entries.into_iter().enumerate() {
+        let doc = doc as u32;
+        let text = utils::html_to_text(content_html);
+
+        let mut frequencies: BTreeMap<String, u32> = BTreeMap::new();
+        for token in tokenize(title) {
+            *frequencies.entry(token).or_default() += TITLE_WEIGHT;
+        }
+        let body_tokens = tokenize(&text);
+        let len = body_tokens.len() as u32;
+        for token in body_tokens {
+            *frequencies.entry(token).or_default() += 1;
+        }
+        for (token, tf) in frequencies {
+            index.terms.entry(token).or_default().push((doc, tf));
+        }
+
+        index.docs.push((slug, title.to_string(), text, len));
+    }
+    index
+}
+
+pub fn build_posts_search_index(posts: &[Post]) -> SearchIndexData {
+    build_search_index(
+        posts
+            .iter()
+            .map(|p| (p.slug(), p.title.as_str(), p.content_html.as_str())),
+    )
+}
+
+impl ToTokens for SearchIndexData {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let docs = self.docs.iter().map(|(slug, title, text, len)| {
+            quote::quote! {
+                aoike::search::SearchDoc {
+                    slug: #slug,
+                    title: #title,
+                    text: #text,
+                    len: #len,
+                }
+            }
+        });
+        let terms = self.terms.iter().map(|(term, postings)| {
+            let postings = postings
+                .iter()
+                .map(|(doc, tf)| quote::quote! { (#doc, #tf) });
+            quote::quote! { (#term, &[#(#postings),*]) }
+        });
+        tokens.extend(quote::quote! {
+            aoike::search::SearchIndex {
+                docs: &[#(#docs),*],
+                terms: &[#(#terms),*],
+            }
+        });
+    }
+}
@@ -0,0 +1,251 @@
+use std::{collections::BTreeMap, path::Path};
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use walkdir::WalkDir;
+
+use crate::{
+    build::{post::Post, report::BuildReport, Entity, ParseOptions},
+    SectionData,
+};
+
+/// A directory under the posts directory, with its optional `_index` page
+#[derive(Debug, Clone)]
+pub struct Section {
+    /// Names of the directories from the posts directory, like `["rust", "async"]`
+    pub path: Vec<String>,
+    /// The `_index.md` (or any other supported extension) in the directory
+    pub index: Option<Post>,
+}
+
+impl Section {
+    pub fn slug(&self) -> String {
+        section_slug(&self.path)
+    }
+    /// Title of the `_index` page, or the directory name
+    pub fn title(&self) -> String {
+        match &self.index {
+            Some(index) => index.title.clone(),
+            None => self.path.last().cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// The slugified directory names joined by `/`
+pub fn section_slug(path: &[String]) -> String {
+    path.iter().map(slug::slugify).collect::<Vec<_>>().join("/")
+}
+
+/// `_index.md`, `_index.typ`... are the pages of the sections instead of posts
+pub fn is_section_index(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .file_stem()
+        .is_some_and(|stem| stem == "_index")
+}
+
+/// The directories between `root` and the file at `path`
+pub(crate) fn section_path(root: &Path, path: &Path) -> Vec<String> {
+    path.strip_prefix(root)
+        .ok()
+        .and_then(Path::parent)
+        .map(|dir| {
+            dir.components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse the `_index` pages of the sections under `dir`
+///
+/// Only the sections with an `_index` page are returned, the others are derived from
+/// the posts by [`collect_sections`].
+pub fn parse_sections(dir: impl AsRef<Path>) -> Vec<Section> {
+    parse_sections_with_options(dir, &ParseOptions::default())
+}
+
+/// [`parse_sections`] with the parsers and date sources in `options`
+///
+/// The errors are emitted as `cargo:warning`, and panic in strict mode like
+/// [`parse_posts_with_options`](crate::build::parse_posts_with_options).
+pub fn parse_sections_with_options(dir: impl AsRef<Path>, options: &ParseOptions) -> Vec<Section> {
+    let dir = dir.as_ref();
+    let git_times = crate::build::git::GitTimes::collect(dir);
+
+    let mut report = BuildReport::default();
+    let mut sections = Vec::new();
+    let entries = WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|entry| !crate::build::is_hidden(entry))
+        .filter_map(Result::ok);
+    for entry in entries {
+        let path = entry.path();
+        let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+        if !entry.file_type().is_file()
+            || !is_section_index(path)
+            || !options.parsers.supports(extension)
+        {
+            continue;
+        }
+        let section = section_path(dir, path);
+        match Entity::resolve(path, &git_times, &options.date_sources)
+            .map(|entity| entity.with_section(section.clone()))
+            .and_then(|entity| Post::parse(entity, options))
+        {
+            Ok(index) => sections.push(Section {
+                path: section,
+                index: Some(index),
+            }),
+            Err(err) => report.error(path, &err),
+        }
+    }
+
+    report.emit();
+    if options.strict
+        && let Err(err) = report.into_strict()
+    {
+        panic!("{err}");
+    }
+    sections.sort_by(|a, b| a.path.cmp(&b.path));
+    sections
+}
+
+/// Build the section tree from the posts and the `_index` pages in `sections`
+///
+/// `entries` yields `(slug, created_timestamp, section)` of each post, where `section`
+/// is the directory names of the post. Every directory containing a post, directly or
+/// not, is a section. Sections are sorted by slug, and their posts newest first.
+pub fn collect_sections<'a>(
+    entries: impl IntoIterator<Item = (String, i64, &'a [String])>,
+    sections: &[Section],
+) -> Vec<SectionData> {
+    let mut tree = SectionTree::new();
+    for section in sections {
+        if let Some((title, content_html, _)) = insert_section(&mut tree, &section.path) {
+            *title = section.title();
+            if let Some(index) = &section.index {
+                *content_html = index.content_html.clone();
+            }
+        }
+    }
+    for (post_slug, created, section) in entries {
+        if let Some((_, _, posts)) = insert_section(&mut tree, section) {
+            posts.push((created, post_slug));
+        }
+    }
+
+    let slugs = tree.keys().cloned().collect::<Vec<_>>();
+    tree.into_iter()
+        .map(|(slug, (title, content_html, mut posts))| {
+            posts.sort_by_key(|(created, _)| std::cmp::Reverse(*created));
+            let sections = slugs
+                .iter()
+                .filter(|s| {
+                    s.strip_prefix(&slug)
+                        .and_then(|rest| rest.strip_prefix('/'))
+                        .is_some_and(|rest| !rest.contains('/'))
+                })
+                .cloned()
+                .collect();
+            SectionData {
+                slug,
+                title,
+                content_html,
+                posts: posts.into_iter().map(|(_, slug)| slug).collect(),
+                sections,
+            }
+        })
+        .collect()
+}
+
+pub fn collect_post_sections<'a>(
+    posts: impl IntoIterator<Item = &'a Post>,
+    sections: &[Section],
+) -> Vec<SectionData> {
+    collect_sections(
+        posts.into_iter().map(|p| {
+            (
+                p.slug(),
+                p.created.unix_timestamp(),
+                p.entity.section.as_slice(),
+            )
+        }),
+        sections,
+    )
+}
+
+/// (title, content_html, posts)
+type SectionEntry = (String, String, Vec<(i64, String)>);
+type SectionTree = BTreeMap<String, SectionEntry>;
+
+/// Insert the section at `path` along with its ancestors, `None` for the posts directory
+fn insert_section<'a>(tree: &'a mut SectionTree, path: &[String]) -> Option<&'a mut SectionEntry> {
+    if path.is_empty() {
+        return None;
+    }
+    for i in 1..=path.len() {
+        tree.entry(section_slug(&path[..i]))
+            .or_insert_with(|| (path[i - 1].clone(), String::new(), vec![]));
+    }
+    tree.get_mut(&section_slug(path))
+}
+
+impl ToTokens for SectionData {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            slug,
+            title,
+            content_html,
+            posts,
+            sections,
+        } = self;
+        tokens.extend(quote::quote! {
+            aoike::SectionData {
+                slug: #slug.to_string(),
+                title: #title.to_string(),
+                content_html: #content_html.to_string(),
+                posts: vec![#(#posts.to_string()),*],
+                sections: vec![#(#sections.to_string()),*],
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_collect_sections() {
+    let rust = vec!["Rust".to_string()];
+    let rust_async = vec!["Rust".to_string(), "Async Await".to_string()];
+    let sections = collect_sections(
+        [
+            (
+                "rust/async-await/a-md".to_string(),
+                1,
+                rust_async.as_slice(),
+            ),
+            ("rust/b-md".to_string(), 3, rust.as_slice()),
+            ("rust/c-md".to_string(), 2, rust.as_slice()),
+            ("d-md".to_string(), 4, [].as_slice()),
+        ],
+        &[],
+    );
+    assert_eq!(
+        sections,
+        vec![
+            SectionData {
+                slug: "rust".to_string(),
+                title: "Rust".to_string(),
+                content_html: String::new(),
+                posts: vec!["rust/b-md".to_string(), "rust/c-md".to_string()],
+                sections: vec!["rust/async-await".to_string()],
+            },
+            SectionData {
+                slug: "rust/async-await".to_string(),
+                title: "Async Await".to_string(),
+                content_html: String::new(),
+                posts: vec!["rust/async-await/a-md".to_string()],
+                sections: vec![],
+            },
+        ]
+    );
+}
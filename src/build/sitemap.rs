@@ -0,0 +1,253 @@
+use std::path::{Path, PathBuf};
+
+use time::{format_description::well_known::Rfc3339, UtcDateTime};
+
+use crate::build::{post::Post, section, taxonomy, utils};
+
+/// Configuration of `sitemap.xml` and `robots.txt`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SitemapConfig {
+    base_url: String,
+    taxonomies: bool,
+    robots: Vec<RobotsGroup>,
+}
+
+/// A `User-agent` group in `robots.txt`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobotsGroup {
+    pub user_agent: String,
+    pub allow: Vec<String>,
+    pub disallow: Vec<String>,
+}
+
+impl SitemapConfig {
+    /// `base_url` is the url the site is deployed to, like `https://example.com`
+    ///
+    /// By default the taxonomy pages are included, and `robots.txt` allows everything.
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            taxonomies: true,
+            robots: vec![],
+        }
+    }
+    /// Whether to include `/tags`, `/categories` and the pages of their terms
+    pub fn with_taxonomies(mut self, taxonomies: bool) -> Self {
+        self.taxonomies = taxonomies;
+        self
+    }
+    /// Add a `User-agent` group to `robots.txt`, replaces the default allow-all group
+    pub fn with_robots_group(
+        mut self,
+        user_agent: String,
+        allow: Vec<String>,
+        disallow: Vec<String>,
+    ) -> Self {
+        self.robots.push(RobotsGroup {
+            user_agent,
+            allow,
+            disallow,
+        });
+        self
+    }
+}
+
+/// Generate `sitemap.xml` for `/`, `/posts`, every post and section, and the taxonomy pages
+///
+/// `<lastmod>` of a page is the latest `updated` of the posts shown on it.
+pub fn sitemap(posts: &[Post], index: &Post, config: &SitemapConfig) -> String {
+    let latest = |slugs: &[String]| {
+        posts
+            .iter()
+            .filter(|p| slugs.contains(&p.slug()))
+            .map(|p| p.updated)
+            .max()
+    };
+    let posts_updated = posts.iter().map(|p| p.updated).max();
+
+    let mut urls: Vec<(String, Option<UtcDateTime>)> = vec![
+        ("/".to_string(), posts_updated.max(Some(index.updated))),
+        ("/posts".to_string(), posts_updated),
+    ];
+    urls.extend(
+        posts
+            .iter()
+            .map(|p| (format!("/posts/{}", p.slug()), Some(p.updated))),
+    );
+    for section in section::collect_post_sections(posts.iter(), &[]) {
+        let prefix = format!("{}/", section.slug);
+        let updated = posts
+            .iter()
+            .filter(|p| p.slug().starts_with(&prefix))
+            .map(|p| p.updated)
+            .max();
+        urls.push((format!("/posts/{}", section.slug), updated));
+    }
+
+    if config.taxonomies {
+        for (base, terms) in [
+            ("/tags", taxonomy::collect_tags(posts.iter())),
+            ("/categories", taxonomy::collect_categories(posts.iter())),
+        ] {
+            if terms.is_empty() {
+                continue;
+            }
+            let all = terms
+                .iter()
+                .flat_map(|t| t.posts.iter().cloned())
+                .collect::<Vec<_>>();
+            urls.push((base.to_string(), latest(&all)));
+            urls.extend(
+                terms
+                    .iter()
+                    .map(|t| (format!("{base}/{}", t.slug), latest(&t.posts))),
+            );
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    out.push('\n');
+    out.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    out.push('\n');
+    for (path, lastmod) in urls {
+        out.push_str("<url>\n");
+        out.push_str(&format!(
+            "<loc>{}</loc>\n",
+            utils::escape_xml(&format!("{}{}", config.base_url, path))
+        ));
+        if let Some(lastmod) = lastmod {
+            out.push_str(&format!(
+                "<lastmod>{}</lastmod>\n",
+                lastmod.format(&Rfc3339).unwrap()
+            ));
+        }
+        out.push_str("</url>\n");
+    }
+    out.push_str("</urlset>\n");
+    out
+}
+
+/// Generate `robots.txt` pointing to the sitemap
+pub fn robots(config: &SitemapConfig) -> String {
+    let default_group = [RobotsGroup {
+        user_agent: "*".to_string(),
+        allow: vec!["/".to_string()],
+        disallow: vec![],
+    }];
+    let groups = if config.robots.is_empty() {
+        &default_group[..]
+    } else {
+        &config.robots[..]
+    };
+
+    let mut out = String::new();
+    for group in groups {
+        out.push_str(&format!("User-agent: {}\n", group.user_agent));
+        for path in &group.allow {
+            out.push_str(&format!("Allow: {path}\n"));
+        }
+        for path in &group.disallow {
+            out.push_str(&format!("Disallow: {path}\n"));
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!("Sitemap: {}/sitemap.xml\n", config.base_url));
+    out
+}
+
+/// Write `sitemap.xml` and `robots.txt` into `dir`
+///
+/// Files are only rewritten when their content changes.
+pub fn write_sitemap(
+    posts: &[Post],
+    index: &Post,
+    config: &SitemapConfig,
+    dir: impl AsRef<Path>,
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let mut paths = Vec::new();
+    for (name, content) in [
+        ("sitemap.xml", sitemap(posts, index, config)),
+        ("robots.txt", robots(config)),
+    ] {
+        let path = dir.join(name);
+        utils::write_if_changed(&path, &content)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// `<link>`s for trunk to copy the files written by [`write_sitemap`] to the root of the output
+pub fn get_sitemap_trunk_data(dir: impl AsRef<Path>) -> String {
+    let dir = dir.as_ref();
+    ["sitemap.xml", "robots.txt"]
+        .iter()
+        .map(|name| {
+            format!(
+                r#"<link rel="copy-file" href="{}" data-trunk>"#,
+                dir.join(name).to_string_lossy()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+#[test]
+fn test_sitemap() {
+    use crate::build::post::test_post;
+
+    let date = |ts| UtcDateTime::from_unix_timestamp(ts).unwrap();
+    let mut old = test_post("old.md", "<h1>Old</h1>");
+    old.entity.updated = date(1704153600);
+    old.meta.tags = vec!["Rust Lang".to_string()];
+    let mut new = test_post("rust/new.md", "<h1>New</h1>");
+    new.entity.section = vec!["rust".to_string()];
+    new.entity.updated = date(1704240000);
+    let index = test_post("index.md", "<h1>Index</h1>");
+
+    let config = SitemapConfig::new("https://example.com/".to_string());
+    let xml = sitemap(&[old.clone(), new.clone()], &index, &config);
+    let urls = xml
+        .split("<url>\n")
+        .skip(1)
+        .map(|url| {
+            url.trim_end_matches("</urlset>\n")
+                .trim_end_matches("</url>\n")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        [
+            "<loc>https://example.com/</loc>\n<lastmod>2024-01-03T00:00:00Z</lastmod>\n",
+            "<loc>https://example.com/posts</loc>\n<lastmod>2024-01-03T00:00:00Z</lastmod>\n",
+            "<loc>https://example.com/posts/old-md</loc>\n<lastmod>2024-01-02T00:00:00Z</lastmod>\n",
+            "<loc>https://example.com/posts/rust/new-md</loc>\n<lastmod>2024-01-03T00:00:00Z</lastmod>\n",
+            "<loc>https://example.com/posts/rust</loc>\n<lastmod>2024-01-03T00:00:00Z</lastmod>\n",
+            "<loc>https://example.com/tags</loc>\n<lastmod>2024-01-02T00:00:00Z</lastmod>\n",
+            "<loc>https://example.com/tags/rust-lang</loc>\n<lastmod>2024-01-02T00:00:00Z</lastmod>\n",
+        ]
+    );
+
+    let config = config.with_taxonomies(false);
+    assert!(!sitemap(&[old, new], &index, &config).contains("/tags"));
+}
+
+#[cfg(test)]
+#[test]
+fn test_robots() {
+    let config = SitemapConfig::new("https://example.com".to_string());
+    assert_eq!(
+        robots(&config),
+        "User-agent: *\nAllow: /\n\nSitemap: https://example.com/sitemap.xml\n"
+    );
+
+    let config = config.with_robots_group("GPTBot".to_string(), vec![], vec!["/".to_string()]);
+    assert_eq!(
+        robots(&config),
+        "User-agent: GPTBot\nDisallow: /\n\nSitemap: https://example.com/sitemap.xml\n"
+    );
+}
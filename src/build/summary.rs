@@ -0,0 +1,134 @@
+use regex::Regex;
+use scraper::{ElementRef, Html, Node};
+
+use crate::build::utils;
+
+/// How to extract the summary of a post without an explicit one
+///
+/// An explicit summary is the frontmatter `summary`, or the content before the
+/// `<!-- more -->` marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryStrategy {
+    /// The first top-level blocks with about this many characters of text, a block
+    /// is never cut unless the first one alone is too long
+    Characters(usize),
+    /// The first this many paragraphs
+    Paragraphs(usize),
+}
+
+impl Default for SummaryStrategy {
+    fn default() -> Self {
+        Self::Characters(200)
+    }
+}
+
+/// The content before `<!-- more -->`, if there is the marker
+pub fn split_more(content_html: &str) -> Option<&str> {
+    let re = Regex::new(r"(?i)<!--\s*more\s*-->").unwrap();
+    re.find(content_html).map(|m| &content_html[..m.start()])
+}
+
+/// The summary of a post, from the frontmatter `summary`, the `<!-- more -->` marker
+/// or `strategy`, in that order
+pub fn summary_html(
+    content_html: &str,
+    summary: Option<&str>,
+    strategy: SummaryStrategy,
+) -> String {
+    if let Some(summary) = summary {
+        return utils::markdown_to_html(summary);
+    }
+    if let Some(before) = split_more(content_html) {
+        // 重新序列化一遍，补全 marker 前未闭合的标签
+        return utils::remove_html_tag(before, &["h1"]);
+    }
+
+    let content_html = utils::remove_html_tag(content_html, &["h1"]);
+    let fragment = Html::parse_fragment(&content_html);
+    let blocks = fragment
+        .root_element()
+        .children()
+        .filter_map(|node| match node.value() {
+            Node::Element(_) => {
+                ElementRef::wrap(node).map(|e| (e.html(), text_len(e), e.value().name() == "p"))
+            }
+            Node::Text(text) if !text.trim().is_empty() => {
+                let len = text.chars().filter(|c| !c.is_whitespace()).count();
+                Some((utils::escape_xml(text), len, false))
+            }
+            _ => None,
+        });
+
+    let mut out = Vec::new();
+    match strategy {
+        SummaryStrategy::Characters(max) => {
+            let mut total = 0;
+            for (html, len, _) in blocks {
+                if total == 0 && len > max {
+                    out.push(utils::extract_html_summary(&html, max));
+                    break;
+                }
+                if total > 0 && total + len > max {
+                    break;
+                }
+                total += len;
+                out.push(html);
+            }
+        }
+        SummaryStrategy::Paragraphs(n) => {
+            out.extend(
+                blocks
+                    .filter(|(_, _, is_p)| *is_p)
+                    .take(n)
+                    .map(|(html, _, _)| html),
+            );
+        }
+    }
+    out.join("\n")
+}
+
+fn text_len(element: ElementRef) -> usize {
+    element
+        .text()
+        .flat_map(|s| s.chars())
+        .filter(|c| !c.is_whitespace())
+        .count()
+}
+
+#[cfg(test)]
+#[test]
+fn test_summary_html() {
+    let html = "<h1>Title</h1>\n<p>first</p>\n<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n<p>second</p>\n<pre><code>long code block</code></pre>";
+
+    assert_eq!(
+        summary_html(html, None, SummaryStrategy::Paragraphs(1)),
+        "<p>first</p>"
+    );
+    assert_eq!(
+        summary_html(html, None, SummaryStrategy::Paragraphs(2)),
+        "<p>first</p>\n<p>second</p>"
+    );
+    // 不会在列表中间截断
+    assert_eq!(
+        summary_html(html, None, SummaryStrategy::Characters(6)),
+        "<p>first</p>"
+    );
+    assert_eq!(
+        summary_html(html, None, SummaryStrategy::Characters(13)),
+        "<p>first</p>\n<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n<p>second</p>"
+    );
+    assert_eq!(
+        summary_html("<p>abcdef</p>", None, SummaryStrategy::Characters(3)),
+        "<p>abc...</p>"
+    );
+
+    let html = "<h1>Title</h1>\n<p>intro</p>\n<!-- more -->\n<p>rest</p>";
+    assert_eq!(
+        summary_html(html, None, SummaryStrategy::default()),
+        "<p>intro</p>"
+    );
+    assert_eq!(
+        summary_html(html, Some("*explicit*"), SummaryStrategy::default()),
+        "<p><em>explicit</em></p>\n"
+    );
+}
@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+
+use crate::{build::post::Post, TaxonomyTerm};
+
+/// Aggregate the terms of a taxonomy over all posts
+///
+/// `entries` yields `(slug, created_timestamp, terms)` of each post.
+/// Terms are merged by their slug, and sorted by post count (then name),
+/// the posts of each term are sorted newest first.
+pub fn collect_terms<'a>(
+    entries: impl IntoIterator<Item = (String, i64, &'a [String])>,
+) -> Vec<TaxonomyTerm> {
+    let mut terms: BTreeMap<String, (String, Vec<(i64, String)>)> = BTreeMap::new();
+    for (post_slug, created, names) in entries {
+        for name in names {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let (_, posts) = terms
+                .entry(slug::slugify(name))
+                .or_insert_with(|| (name.to_string(), vec![]));
+            if !posts.iter().any(|(_, slug)| slug == &post_slug) {
+                posts.push((created, post_slug.clone()));
+            }
+        }
+    }
+
+    let mut terms = terms
+        .into_iter()
+        .map(|(slug, (name, mut posts))| {
+            posts.sort_by_key(|(created, _)| std::cmp::Reverse(*created));
+            TaxonomyTerm {
+                name,
+                slug,
+                posts: posts.into_iter().map(|(_, slug)| slug).collect(),
+            }
+        })
+        .collect::<Vec<_>>();
+    terms.sort_by(|a, b| b.posts.len().cmp(&a.posts.len()).then(a.name.cmp(&b.name)));
+    terms
+}
+
+pub fn collect_tags<'a>(posts: impl IntoIterator<Item = &'a Post>) -> Vec<TaxonomyTerm> {
+    collect_terms(
+        posts
+            .into_iter()
+            .map(|p| (p.slug(), p.created.unix_timestamp(), p.meta.tags.as_slice())),
+    )
+}
+
+pub fn collect_categories<'a>(posts: impl IntoIterator<Item = &'a Post>) -> Vec<TaxonomyTerm> {
+    collect_terms(posts.into_iter().map(|p| {
+        (
+            p.slug(),
+            p.created.unix_timestamp(),
+            p.meta.categories.as_slice(),
+        )
+    }))
+}
+
+impl ToTokens for TaxonomyTerm {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self { name, slug, posts } = self;
+        tokens.extend(quote::quote! {
+            aoike::TaxonomyTerm {
+                name: #name.to_string(),
+                slug: #slug.to_string(),
+                posts: vec![#(#posts.to_string()),*],
+            }
+        });
+    }
+}
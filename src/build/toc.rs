@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+
+use html5ever::{local_name, ns, QualName};
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use scraper::{Html, Node};
+
+use crate::TocEntry;
+
+/// Give every `<h2>`-`<h6>` without an `id` one slugified from its text, and collect
+/// them into a nested table of contents
+///
+/// Existing ids (like the ones from `{#id}` heading attributes) are kept, duplicated
+/// slugs get a `-1`, `-2`... suffix. The html is left untouched if nothing changes.
+pub fn add_heading_ids(html: &str) -> (String, Vec<TocEntry>) {
+    let mut fragment = Html::parse_fragment(html);
+
+    let mut used = fragment
+        .root_element()
+        .descendent_elements()
+        .filter_map(|e| e.value().attr("id").map(str::to_string))
+        .collect::<HashSet<_>>();
+
+    let mut headings = Vec::new();
+    let mut missing_ids = Vec::new();
+    for element in fragment.root_element().descendent_elements() {
+        let level = match element.value().name() {
+            "h2" => 2,
+            "h3" => 3,
+            "h4" => 4,
+            "h5" => 5,
+            "h6" => 6,
+            _ => continue,
+        };
+        let title = element.text().collect::<Vec<_>>().join("");
+        let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        let id = match element.value().attr("id") {
+            Some(id) => id.to_string(),
+            None => {
+                let base = slugify(&title);
+                let mut id = base.clone();
+                let mut n = 0;
+                while used.contains(&id) {
+                    n += 1;
+                    id = format!("{base}-{n}");
+                }
+                used.insert(id.clone());
+                missing_ids.push((element.id(), id.clone()));
+                id
+            }
+        };
+        headings.push(TocEntry {
+            level,
+            id,
+            title,
+            children: vec![],
+        });
+    }
+
+    let html = if missing_ids.is_empty() {
+        html.to_string()
+    } else {
+        for (node_id, id) in missing_ids {
+            if let Some(mut node) = fragment.tree.get_mut(node_id)
+                && let Node::Element(element) = node.value()
+            {
+                element
+                    .attrs
+                    .push((QualName::new(None, ns!(), local_name!("id")), id.into()));
+            }
+        }
+        fragment.root_element().inner_html()
+    };
+
+    let mut toc = Vec::new();
+    for heading in headings {
+        insert(&mut toc, heading);
+    }
+    (html, toc)
+}
+
+/// Put `entry` under the last entry of a higher level
+fn insert(entries: &mut Vec<TocEntry>, entry: TocEntry) {
+    match entries.last_mut() {
+        Some(last) if last.level < entry.level => insert(&mut last.children, entry),
+        _ => entries.push(entry),
+    }
+}
+
+/// Lowercase, with the runs of characters other than letters, digits and `_` replaced
+/// by `-`, CJK characters are kept as is
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() || c == '_' {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+impl ToTokens for TocEntry {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            level,
+            id,
+            title,
+            children,
+        } = self;
+        tokens.extend(quote::quote! {
+            aoike::TocEntry {
+                level: #level,
+                id: #id.to_string(),
+                title: #title.to_string(),
+                children: vec![#(#children),*],
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_add_heading_ids() {
+    let html = r#"<h1>Title</h1>
+<h2>Hello, World!</h2>
+<h3>中文 标题</h3>
+<h4 id="custom">Deep</h4>
+<h2>Hello, World!</h2>
+<h3><code>code</code> span</h3>
+<h2>!!!</h2>"#;
+    let (html, toc) = add_heading_ids(html);
+
+    assert!(html.contains(r#"<h1>Title</h1>"#));
+    assert!(html.contains(r#"<h2 id="hello-world">Hello, World!</h2>"#));
+    assert!(html.contains(r#"<h3 id="中文-标题">中文 标题</h3>"#));
+    assert!(html.contains(r#"<h4 id="custom">Deep</h4>"#));
+    assert!(html.contains(r#"<h2 id="hello-world-1">Hello, World!</h2>"#));
+    assert!(html.contains(r#"<h3 id="code-span"><code>code</code> span</h3>"#));
+    assert!(html.contains(r#"<h2 id="section">!!!</h2>"#));
+
+    let entry = |level, id: &str, title: &str, children| TocEntry {
+        level,
+        id: id.to_string(),
+        title: title.to_string(),
+        children,
+    };
+    assert_eq!(
+        toc,
+        vec![
+            entry(
+                2,
+                "hello-world",
+                "Hello, World!",
+                vec![entry(
+                    3,
+                    "中文-标题",
+                    "中文 标题",
+                    vec![entry(4, "custom", "Deep", vec![])]
+                )]
+            ),
+            entry(
+                2,
+                "hello-world-1",
+                "Hello, World!",
+                vec![entry(3, "code-span", "code span", vec![])]
+            ),
+            entry(2, "section", "!!!", vec![]),
+        ]
+    );
+
+    let html = "<p>no headings</p>";
+    assert_eq!(add_heading_ids(html), (html.to_string(), vec![]));
+}
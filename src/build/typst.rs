@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+use ::typst::{
+    diag::{FileError, FileResult, Severity, SourceDiagnostic},
+    foundations::{Bytes, Datetime},
+    syntax::{FileId, Source, VirtualPath},
+    text::{Font, FontBook},
+    utils::LazyHash,
+    Feature, Library, LibraryExt, World, WorldExt,
+};
+use typst_html::HtmlDocument;
+
+static LIBRARY: LazyLock<LazyHash<Library>> = LazyLock::new(|| {
+    LazyHash::new(
+        Library::builder()
+            .with_features([Feature::Html].into_iter().collect())
+            .build(),
+    )
+});
+
+/// The fonts bundled with `typst-assets`
+static FONTS: LazyLock<(LazyHash<FontBook>, Vec<Font>)> = LazyLock::new(|| {
+    let fonts = typst_assets::fonts()
+        .flat_map(|data| Font::iter(Bytes::new(data)))
+        .collect::<Vec<_>>();
+    (LazyHash::new(FontBook::from_fonts(&fonts)), fonts)
+});
+
+/// A diagnostic reported by the typst compiler
+#[derive(Debug, Clone)]
+pub struct TypstDiagnostic {
+    pub severity: Severity,
+    /// The file the diagnostic points to, `None` for the ones without a location
+    pub file: Option<PathBuf>,
+    /// 1-based line
+    pub line: Option<usize>,
+    /// 1-based column
+    pub column: Option<usize>,
+    pub message: String,
+    pub hints: Vec<String>,
+}
+
+impl fmt::Display for TypstDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match (&self.file, self.line, self.column) {
+            (Some(file), Some(line), Some(column)) => {
+                write!(f, "{}:{line}:{column}: ", file.display())?
+            }
+            (Some(file), _, _) => write!(f, "{}: ", file.display())?,
+            _ => {}
+        }
+        write!(f, "{severity}: {}", self.message)?;
+        for hint in &self.hints {
+            write!(f, "\n  hint: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Failed to compile a typst file, with all the errors reported by the compiler
+#[derive(Debug, Clone)]
+pub struct TypstError {
+    pub diagnostics: Vec<TypstDiagnostic>,
+}
+
+impl fmt::Display for TypstError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "failed to compile typst")?;
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{diagnostic}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TypstError {}
+
+/// A typst [`World`] reading files under `root`
+///
+/// Packages are not supported, `@preview/...` imports fail with an error.
+pub struct TypstWorld {
+    root: PathBuf,
+    main: FileId,
+    sources: Mutex<HashMap<FileId, Source>>,
+}
+
+impl TypstWorld {
+    /// `main` must be inside `root`
+    pub fn new(root: impl AsRef<Path>, main: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let root = root.as_ref().canonicalize()?;
+        let main = main.as_ref().canonicalize()?;
+        let vpath = VirtualPath::within_root(&main, &root).ok_or_else(|| {
+            anyhow::anyhow!("{} is not inside {}", main.display(), root.display())
+        })?;
+        Ok(Self {
+            root,
+            main: FileId::new(None, vpath),
+            sources: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn path(&self, id: FileId) -> FileResult<PathBuf> {
+        if id.package().is_some() {
+            return Err(FileError::Other(Some("packages are not supported".into())));
+        }
+        id.vpath()
+            .resolve(&self.root)
+            .ok_or(FileError::AccessDenied)
+    }
+
+    fn read(&self, id: FileId) -> FileResult<Vec<u8>> {
+        let path = self.path(id)?;
+        std::fs::read(&path).map_err(|err| FileError::from_io(err, &path))
+    }
+
+    fn diagnostic(&self, diagnostic: &SourceDiagnostic) -> TypstDiagnostic {
+        let span = diagnostic.span;
+        let file = span.id().and_then(|id| self.path(id).ok());
+        let position = span.id().zip(self.range(span)).and_then(|(id, range)| {
+            let source = self.source(id).ok()?;
+            let lines = source.lines();
+            Some((
+                lines.byte_to_line(range.start)? + 1,
+                lines.byte_to_column(range.start)? + 1,
+            ))
+        });
+        TypstDiagnostic {
+            severity: diagnostic.severity,
+            file,
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+            message: diagnostic.message.to_string(),
+            hints: diagnostic.hints.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    /// Compile the main file to html
+    ///
+    /// Warnings are printed as `cargo:warning`, errors are returned as a [`TypstError`].
+    pub fn compile_to_html(&self) -> Result<String, anyhow::Error> {
+        let warned = ::typst::compile::<HtmlDocument>(self);
+        for warning in &warned.warnings {
+            println!("cargo:warning={}", self.diagnostic(warning));
+        }
+        let into_error = |errors: &[SourceDiagnostic]| TypstError {
+            diagnostics: errors.iter().map(|e| self.diagnostic(e)).collect(),
+        };
+        let document = warned.output.map_err(|e| into_error(&e))?;
+        Ok(typst_html::html(&document).map_err(|e| into_error(&e))?)
+    }
+}
+
+impl World for TypstWorld {
+    fn library(&self) -> &LazyHash<Library> {
+        &LIBRARY
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        &FONTS.0
+    }
+
+    fn main(&self) -> FileId {
+        self.main
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        if let Some(source) = self.sources.lock().unwrap().get(&id) {
+            return Ok(source.clone());
+        }
+        let text = String::from_utf8(self.read(id)?).map_err(|_| FileError::InvalidUtf8)?;
+        let source = Source::new(id, text);
+        self.sources.lock().unwrap().insert(id, source.clone());
+        Ok(source)
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        self.read(id).map(Bytes::new)
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        FONTS.1.get(index).cloned()
+    }
+
+    fn today(&self, offset: Option<i64>) -> Option<Datetime> {
+        let offset = time::UtcOffset::from_hms(offset.unwrap_or(0).try_into().ok()?, 0, 0).ok()?;
+        let now = time::OffsetDateTime::now_utc().to_offset(offset);
+        Datetime::from_ymd(now.year(), now.month().into(), now.day())
+    }
+}
+
+/// Compile the typst file at `path` to html, with the files under `root` accessible
+pub fn compile_to_html(
+    path: impl AsRef<Path>,
+    root: impl AsRef<Path>,
+) -> Result<String, anyhow::Error> {
+    TypstWorld::new(root, path)?.compile_to_html()
+}
+
+#[cfg(test)]
+#[test]
+fn test_compile_error_diagnostics() {
+    let dir = crate::build::utils::temp_dir_with(&[("broken.typ", "= Title\n\n#unknown-func()\n")]);
+    let path = dir.path().join("broken.typ");
+
+    let err = compile_to_html(&path, dir.path()).unwrap_err();
+    let err = err.downcast_ref::<TypstError>().unwrap();
+    let diagnostic = &err.diagnostics[0];
+    assert_eq!(
+        diagnostic.file.as_deref(),
+        Some(path.canonicalize().unwrap().as_path())
+    );
+    assert_eq!((diagnostic.line, diagnostic.column), (Some(3), Some(2)));
+    assert!(diagnostic.message.contains("unknown variable"));
+}
@@ -1,7 +1,9 @@
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use regex::Regex;
-use time::UtcDateTime;
+use scraper::{Html, Selector};
+
+use crate::build::markdown::{self, MarkdownOptions};
 
 pub fn patch_file(
     path: impl AsRef<Path>,
@@ -60,60 +62,206 @@ pub fn inject_str(
     }
 }
 
+/// Write `content` to `path` only if it differs from the existing content,
+/// so that files watched by `cargo:rerun-if-changed` don't retrigger the build
+pub fn write_if_changed(path: impl AsRef<Path>, content: &str) -> Result<(), anyhow::Error> {
+    let path = path.as_ref();
+    if std::fs::read_to_string(path).ok().as_deref() != Some(content) {
+        std::fs::write(path, content)?;
+    }
+    Ok(())
+}
+
+pub fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Convert markdown to html with the default [`MarkdownOptions`]
+pub fn markdown_to_html(markdown: &str) -> String {
+    markdown::to_html(markdown, &MarkdownOptions::default())
+}
+
+/// Local files referenced by the html, relative to the file the html comes from
+///
+/// Covers `src`, `srcset` and `poster` of any element (`<img>`, `<source>`, `<video>`...),
+/// and `href` of `<a>` pointing to a non-html file like a pdf. Remote urls, absolute
+/// paths, anchors and `data:` urls are skipped.
 pub fn get_ref_paths(html: &str) -> Vec<String> {
-    // 排除 data:image 开头的内联数据
-    // Rust's regex does not support lookahead/lookbehind, so match all src="..." and filter in code.
-    let re = Regex::new(r#"src="([^"]+)""#).unwrap();
-    re.captures_iter(html)
-        .map(|cap| cap.get(1).unwrap().as_str().to_string())
-        .filter(|s| !s.starts_with("data:"))
-        .collect()
+    let html = Html::parse_fragment(html);
+    let mut paths: Vec<String> = Vec::new();
+    let mut push = |url: &str| {
+        if let Some(path) = local_ref_path(url)
+            && !paths.iter().any(|p| p == path)
+        {
+            paths.push(path.to_string());
+        }
+    };
+
+    for element in html.root_element().descendent_elements() {
+        let element = element.value();
+        for attr in ["src", "poster"] {
+            if let Some(url) = element.attr(attr) {
+                push(url);
+            }
+        }
+        if let Some(srcset) = element.attr("srcset") {
+            // `a.png 1x, b.png 2x`
+            srcset
+                .split(',')
+                .filter_map(|candidate| candidate.split_whitespace().next())
+                .for_each(&mut push);
+        }
+        if element.name() == "a"
+            && let Some(href) = element.attr("href")
+            && local_ref_path(href)
+                .and_then(|path| Path::new(path).extension())
+                .is_some_and(|ext| !["html", "htm", "md", "typ"].contains(&&*ext.to_string_lossy()))
+        {
+            push(href);
+        }
+    }
+    paths
+}
+
+/// The path part of `url` if it refers to a local file by a relative path
+pub(crate) fn local_ref_path(url: &str) -> Option<&str> {
+    let url = url.trim();
+    let path = url.split(['#', '?']).next().unwrap_or_default();
+    let has_scheme = path.split_once(':').is_some_and(|(scheme, _)| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    });
+    if path.is_empty() || has_scheme || path.starts_with('/') {
+        return None;
+    }
+    Some(path)
 }
 
+/// `a/./b/../c` -> `a/c`, without touching the file system
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// `my%20post.md` -> `my post.md`
+pub(crate) fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Inner html of the first `<tag>` element
 pub fn get_tag_content(html: &str, tag: &str) -> Option<String> {
-    let re = Regex::new(&format!("<{tag}>(?s:(.*?))</{tag}>")).unwrap();
-    let cap = re.captures_iter(html).next();
-    cap.map(|cap| cap.get(1).unwrap().as_str().trim().to_string())
+    let selector = Selector::parse(tag).ok()?;
+    let html = Html::parse_document(html);
+    html.select(&selector)
+        .next()
+        .map(|element| element.inner_html().trim().to_string())
 }
 
+/// Text of the first `<h1>`, with the inner tags stripped
 pub fn get_html_h1(html: &str) -> Option<String> {
-    get_tag_content(html, "h1")
+    let selector = Selector::parse("h1").unwrap();
+    let html = Html::parse_fragment(html);
+    html.select(&selector)
+        .next()
+        .map(|element| element.text().collect::<String>().trim().to_string())
+        .filter(|title| !title.is_empty())
 }
 
+/// Remove all the `tags` elements along with their content
 pub fn remove_html_tag(html: &str, tags: &[&str]) -> String {
     // tags: ["h1", "h2"] etc.
     if tags.is_empty() {
         return html.to_string();
     }
 
-    // 构建标准正则表达式（不使用反向引用，因为 Rust 的标准 regex 不支持反向引用）
-    // 针对每一个 tag 单独生成正则，并分别替换
-    let mut result = html.to_string();
-
-    for &tag in tags {
-        let tag_escaped = regex::escape(tag);
-
-        // 匹配形如 <tag ...>...</tag> （非嵌套）、以及自闭合 <tag ... />
-        // 非贪婪匹配内容，适合移除简单的 h1/h2 结构
-        let open_close_pat = format!(r"(?is)<{tag}\b[^>]*>.*?</{tag}\s*>", tag = tag_escaped);
-        let self_close_pat = format!(r"(?is)<{tag}\b[^>]*/\s*>", tag = tag_escaped);
-
-        let open_close_re = regex::RegexBuilder::new(&open_close_pat)
-            .case_insensitive(true)
-            .dot_matches_new_line(true)
-            .build()
-            .unwrap();
-        let self_close_re = regex::RegexBuilder::new(&self_close_pat)
-            .case_insensitive(true)
-            .dot_matches_new_line(true)
-            .build()
-            .unwrap();
-
-        result = open_close_re.replace_all(&result, "").to_string();
-        result = self_close_re.replace_all(&result, "").to_string();
+    let mut fragment = Html::parse_fragment(html);
+    let ids = fragment
+        .root_element()
+        .descendent_elements()
+        .filter(|element| {
+            tags.iter()
+                .any(|tag| element.value().name().eq_ignore_ascii_case(tag))
+        })
+        .map(|element| element.id())
+        .collect::<Vec<_>>();
+    for id in ids {
+        if let Some(mut node) = fragment.tree.get_mut(id) {
+            node.detach();
+        }
     }
 
-    result.trim().to_string()
+    fragment.root_element().inner_html().trim().to_string()
+}
+
+/// 提取 HTML 中的纯文本，连续的空白会被合并为一个空格
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 /// 从 HTML 字符串中提取前 `max_text_len` 个字符的摘要,不破坏标签结构
@@ -183,7 +331,7 @@ pub fn extract_html_summary(html: &str, max_text_len: usize) -> String {
                 }
             } else {
                 // 补全未关闭的标签
-                out.extend(std::iter::repeat('.').take(3));
+                out.extend(std::iter::repeat_n('.', 3));
                 for tag in tag_stack.into_iter().rev() {
                     out.push_str(&format!("</{}>", tag));
                 }
@@ -195,43 +343,62 @@ pub fn extract_html_summary(html: &str, max_text_len: usize) -> String {
     out.trim().to_string()
 }
 
-fn parse_git_ts(output: std::io::Result<std::process::Output>) -> i64 {
-    match output {
-        Ok(out) if out.status.success() => {
-            let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
-            s.parse::<i64>().unwrap_or(0)
-        }
-        _ => 0,
+/// A new temporary directory with the `(path, content)` files written into it, removed
+/// when dropped
+#[cfg(test)]
+pub(crate) fn temp_dir_with(files: &[(&str, &str)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
     }
+    dir
 }
 
-pub fn git_updated_ts(path: &Path) -> i64 {
-    use std::process::Command;
-    let output = Command::new("git")
-        .arg("log")
-        .arg("-1")
-        .arg("--format=%ct")
-        .arg(path)
-        .output();
-    parse_git_ts(output)
-}
-
-pub fn git_updated_datetime(path: &Path) -> UtcDateTime {
-    UtcDateTime::from_unix_timestamp(git_updated_ts(path)).unwrap()
-}
-
-pub fn git_created_ts(path: &Path) -> i64 {
-    use std::process::Command;
-    let output = Command::new("git")
-        .arg("log")
-        .arg("--diff-filter=A")
-        .arg("-1")
-        .arg("--format=%ct")
-        .arg(path)
-        .output();
-    parse_git_ts(output)
-}
+#[cfg(test)]
+#[test]
+fn test_html_helpers() {
+    // 带属性、嵌套标签的 h1
+    let html = r#"<h1 id="x">Hello <code>world</code></h1><p>text</p>"#;
+    assert_eq!(get_html_h1(html).as_deref(), Some("Hello world"));
+    assert_eq!(
+        get_tag_content(html, "h1").as_deref(),
+        Some("Hello <code>world</code>")
+    );
+    assert_eq!(remove_html_tag(html, &["h1"]), "<p>text</p>");
+    assert_eq!(
+        remove_html_tag(r#"<H1 class="a"><span>a</span></H1><h2>b</h2>"#, &["h1"]),
+        "<h2>b</h2>"
+    );
+    assert_eq!(
+        get_tag_content("<html><body>\n<p>a</p>\n</body></html>", "body").as_deref(),
+        Some("<p>a</p>")
+    );
 
-pub fn git_created_datetime(path: &Path) -> UtcDateTime {
-    UtcDateTime::from_unix_timestamp(git_created_ts(path)).unwrap()
+    let html = r#"
+<img src='single.png'>
+<img src="a.png" srcset="a@2x.png 2x, a@3x.png 3x">
+<picture><source srcset="b.webp" type="image/webp"><img src="b.png"></picture>
+<video poster="poster.jpg"><source src="v.mp4"></video>
+<a href="files/doc.pdf#page=2">pdf</a>
+<a href="other.md">post</a>
+<a href="https://example.com/x.pdf">remote</a>
+<img src="/abs.png"><img src="data:image/png;base64,AAAA"><img src="a.png">
+<pre><code>&lt;img src="not-a-ref.png"&gt;</code></pre>
+"#;
+    assert_eq!(
+        get_ref_paths(html),
+        [
+            "single.png",
+            "a.png",
+            "a@2x.png",
+            "a@3x.png",
+            "b.webp",
+            "b.png",
+            "poster.jpg",
+            "v.mp4",
+            "files/doc.pdf",
+        ]
+    );
 }
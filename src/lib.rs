@@ -1,9 +1,24 @@
 #[cfg(feature = "build")]
 pub mod build;
+pub mod search;
+
+use std::collections::BTreeMap;
 
 pub use time;
 use time::UtcDateTime;
 
+/// A free-form frontmatter value
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaValue {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<MetaValue>),
+    Table(BTreeMap<String, MetaValue>),
+}
+
 #[derive(Clone, PartialEq)]
 pub struct PostData {
     pub title: String,
@@ -12,10 +27,66 @@ pub struct PostData {
     pub content_html: String,
     pub created: UtcDateTime,
     pub updated: UtcDateTime,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    pub draft: bool,
+    /// The source file has changes not committed to git yet
+    pub uncommitted: bool,
+    /// Slugified names of the directories containing the post, `section.join("/")` is
+    /// the slug of its [`SectionData`]
+    pub section: Vec<String>,
+    /// Former slugs of the post, `/posts/{alias}` redirects to it
+    pub aliases: Vec<String>,
+    /// Slugs of the posts linking to this one
+    pub backlinks: Vec<String>,
+    /// Frontmatter keys that are not recognized by aoike
+    pub extra: BTreeMap<String, MetaValue>,
+    /// Table of contents of `<h2>`-`<h6>`
+    pub toc: Vec<TocEntry>,
+}
+
+/// A heading in the table of contents of a post
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    /// `2` for `<h2>`, etc.
+    pub level: u8,
+    /// `id` of the heading element
+    pub id: String,
+    pub title: String,
+    /// Headings of lower levels under this one
+    pub children: Vec<TocEntry>,
+}
+
+/// A term of a taxonomy (a tag or a category) and the posts under it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxonomyTerm {
+    pub name: String,
+    pub slug: String,
+    /// Slugs of the posts, newest first
+    pub posts: Vec<String>,
+}
+
+/// A directory under the posts directory, with the posts and the subsections in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionData {
+    /// Slugified directory names joined by `/`, like `rust/async`
+    pub slug: String,
+    /// Title of the `_index` page, or the directory name
+    pub title: String,
+    /// Content of the `_index` page, empty if there is none
+    pub content_html: String,
+    /// Slugs of the posts directly in the section, newest first
+    pub posts: Vec<String>,
+    /// Slugs of the direct subsections
+    pub sections: Vec<String>,
 }
 
 #[derive(Clone)]
 pub struct Site {
     pub posts: &'static [PostData],
     pub index: &'static PostData,
+    pub tags: &'static [TaxonomyTerm],
+    pub categories: &'static [TaxonomyTerm],
+    pub sections: &'static [SectionData],
+    pub search_index: &'static search::SearchIndex,
 }
@@ -0,0 +1,266 @@
+//! Client-side full text search over the inverted index generated at build time
+//!
+//! Latin text is split into lowercased words, runs of CJK characters are split
+//! into overlapping bigrams (`异步编程` -> `异步`, `步编`, `编程`), so the same
+//! [`tokenize`] can be used both when building the index and when querying it
+//! in the browser without shipping a dictionary.
+
+/// A searchable document, `text` is the plain text of the post used for snippets
+#[derive(Debug, PartialEq)]
+pub struct SearchDoc {
+    pub slug: &'static str,
+    pub title: &'static str,
+    pub text: &'static str,
+    /// Number of tokens in the document
+    pub len: u32,
+}
+
+/// The inverted index
+#[derive(Debug, PartialEq)]
+pub struct SearchIndex {
+    pub docs: &'static [SearchDoc],
+    /// `(term, [(doc, term_frequency)])`, sorted by term
+    pub terms: &'static [(&'static str, &'static [(u32, u32)])],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub doc: &'static SearchDoc,
+    pub score: f32,
+    /// The snippet split into `(text, highlighted)` segments
+    pub snippet: Vec<(&'static str, bool)>,
+}
+
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+const SNIPPET_LEN: usize = 120;
+const SNIPPET_BEFORE: usize = 30;
+
+fn is_cjk(ch: char) -> bool {
+    matches!(ch,
+        '\u{3040}'..='\u{30ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{ac00}'..='\u{d7af}'
+        | '\u{20000}'..='\u{2ffff}'
+    )
+}
+
+/// Split text into search tokens
+pub fn tokenize(text: &str) -> Vec<String> {
+    fn flush_cjk(cjk: &mut Vec<char>, tokens: &mut Vec<String>) {
+        match cjk.len() {
+            0 => {}
+            1 => tokens.push(cjk[0].to_string()),
+            _ => tokens.extend(cjk.windows(2).map(|w| w.iter().collect())),
+        }
+        cjk.clear();
+    }
+    fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+        if !word.is_empty() {
+            tokens.push(std::mem::take(word));
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk = Vec::new();
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            flush_word(&mut word, &mut tokens);
+            cjk.push(ch);
+        } else if ch.is_alphanumeric() {
+            flush_cjk(&mut cjk, &mut tokens);
+            word.extend(ch.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk(&mut cjk, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk, &mut tokens);
+    tokens
+}
+
+impl SearchIndex {
+    fn postings(&self, token: &str) -> Vec<&'static [(u32, u32)]> {
+        let single_cjk = token.chars().count() == 1 && token.chars().all(is_cjk);
+        if single_cjk {
+            // Only bigrams are indexed for CJK runs longer than one character,
+            // so a single character matches every term that contains it
+            self.terms
+                .iter()
+                .filter(|(term, _)| term.contains(token))
+                .map(|(_, postings)| *postings)
+                .collect()
+        } else {
+            self.terms
+                .binary_search_by(|(term, _)| (*term).cmp(token))
+                .map(|i| vec![self.terms[i].1])
+                .unwrap_or_default()
+        }
+    }
+
+    /// Search the index, returns at most `limit` results ranked by BM25
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+        let mut tokens = tokenize(query);
+        tokens.sort();
+        tokens.dedup();
+        if tokens.is_empty() || self.docs.is_empty() {
+            return vec![];
+        }
+
+        let doc_count = self.docs.len() as f32;
+        let avg_len = self.docs.iter().map(|d| d.len as f32).sum::<f32>() / doc_count;
+        let mut scores = vec![0f32; self.docs.len()];
+        for token in &tokens {
+            for postings in self.postings(token) {
+                let df = postings.len() as f32;
+                let idf = (1.0 + (doc_count - df + 0.5) / (df + 0.5)).ln();
+                for &(doc, tf) in postings {
+                    let tf = tf as f32;
+                    let len = self.docs[doc as usize].len as f32;
+                    let norm = tf + BM25_K1 * (1.0 - BM25_B + BM25_B * len / avg_len.max(1.0));
+                    scores[doc as usize] += idf * tf * (BM25_K1 + 1.0) / norm;
+                }
+            }
+        }
+
+        let mut ranked = scores
+            .into_iter()
+            .enumerate()
+            .filter(|(_, score)| *score > 0.0)
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(doc, score)| {
+                let doc = &self.docs[doc];
+                SearchResult {
+                    doc,
+                    score,
+                    snippet: snippet(doc.text, &tokens),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Find all the (ascii case insensitive) byte ranges of `tokens` in `text`
+fn find_matches(text: &str, tokens: &[String]) -> Vec<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut ranges = Vec::new();
+    for token in tokens.iter().filter(|t| !t.is_empty()) {
+        let token = token.as_bytes();
+        let mut start = 0;
+        while start + token.len() <= bytes.len() {
+            if text.is_char_boundary(start)
+                && bytes[start..start + token.len()].eq_ignore_ascii_case(token)
+            {
+                ranges.push((start, start + token.len()));
+                start += token.len();
+            } else {
+                start += 1;
+            }
+        }
+    }
+    ranges.sort();
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Cut a window of `text` around the first match and split it into highlighted segments
+pub fn snippet(text: &'static str, tokens: &[String]) -> Vec<(&'static str, bool)> {
+    let matches = find_matches(text, tokens);
+
+    let char_starts = text.char_indices().map(|(i, _)| i).collect::<Vec<_>>();
+    let first_char = matches
+        .first()
+        .map(|(start, _)| char_starts.partition_point(|&i| i < *start))
+        .unwrap_or(0);
+    let start_char = first_char.saturating_sub(SNIPPET_BEFORE);
+    let end_char = (start_char + SNIPPET_LEN).min(char_starts.len());
+    let start = char_starts.get(start_char).copied().unwrap_or(text.len());
+    let end = char_starts.get(end_char).copied().unwrap_or(text.len());
+
+    let mut segments = Vec::new();
+    if start > 0 {
+        segments.push(("…", false));
+    }
+    let mut cursor = start;
+    for (match_start, match_end) in matches {
+        if match_end <= start || match_start >= end {
+            continue;
+        }
+        let (match_start, match_end) = (match_start.max(start), match_end.min(end));
+        if cursor < match_start {
+            segments.push((&text[cursor..match_start], false));
+        }
+        segments.push((&text[match_start..match_end], true));
+        cursor = match_end;
+    }
+    if cursor < end {
+        segments.push((&text[cursor..end], false));
+    }
+    if end < text.len() {
+        segments.push(("…", false));
+    }
+    segments
+}
+
+#[cfg(test)]
+#[test]
+fn test_tokenize() {
+    assert_eq!(
+        tokenize("Rust 异步编程, async/await!"),
+        vec!["rust", "异步", "步编", "编程", "async", "await"]
+    );
+    assert_eq!(tokenize("池"), vec!["池"]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_search() {
+    static INDEX: SearchIndex = SearchIndex {
+        docs: &[
+            SearchDoc {
+                slug: "a",
+                title: "异步",
+                text: "Rust 异步编程",
+                len: 4,
+            },
+            SearchDoc {
+                slug: "b",
+                title: "同步",
+                text: "同步编程",
+                len: 3,
+            },
+        ],
+        terms: &[
+            ("rust", &[(0, 1)]),
+            ("同步", &[(1, 6)]),
+            ("异步", &[(0, 6)]),
+            ("步编", &[(0, 1), (1, 1)]),
+            ("编程", &[(0, 1), (1, 1)]),
+        ],
+    };
+
+    let results = INDEX.search("异步编程", 10);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].doc.slug, "a");
+    assert_eq!(
+        results[0].snippet,
+        vec![("Rust ", false), ("异步编程", true)]
+    );
+    assert_eq!(INDEX.search("编", 10).len(), 2);
+    assert!(INDEX.search("python", 10).is_empty());
+}